@@ -0,0 +1,198 @@
+use crate::console::ToConsole;
+use crate::network::{PacketTypes, ToNetwork};
+use crate::player::{Item, ItemStack};
+use crate::world::{Chunk, World, WorldError};
+use crate::{c_error, c_info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::num::{NonZeroU32, NonZeroU8};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const BACKUP_DIR: &str = "backups";
+
+/// A backup's name and the unix timestamp it was taken at, cached in [`World::backups`] so
+/// `Command::ListBackups` doesn't need to rescan [`BACKUP_DIR`].
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub name: String,
+    pub timestamp: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("no backup named `{0}`")]
+    NotFound(String),
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("(de)serialization error: {0}")]
+    Serde(#[from] serde_pickle::Error),
+    #[error(transparent)]
+    World(#[from] WorldError),
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlayerSnapshot {
+    name: String,
+    x: f32,
+    y: f32,
+    inventory: [Option<(u8, u8)>; 9],
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorldSnapshot {
+    width: u32,
+    height: u32,
+    chunk_size: u32,
+    spawn_point: u32,
+    spawn_range: u32,
+    chunks: Vec<Chunk>,
+    players: Vec<PlayerSnapshot>,
+}
+
+impl WorldSnapshot {
+    fn capture(world: &World) -> WorldSnapshot {
+        WorldSnapshot {
+            width: world.width,
+            height: world.height,
+            chunk_size: world.chunk_size,
+            spawn_point: world.spawn_point,
+            spawn_range: world.spawn_range.get(),
+            chunks: world.chunks.clone(),
+            players: world
+                .players
+                .iter()
+                .map(|conn| PlayerSnapshot {
+                    name: conn.name.clone(),
+                    x: conn.server_player.x,
+                    y: conn.server_player.y,
+                    inventory: conn.server_player.inventory.map(|stack_maybe| {
+                        stack_maybe.map(|stack| (stack.item.into(), stack.count.get()))
+                    }),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn backup_path(name: &str) -> PathBuf {
+    PathBuf::from(BACKUP_DIR).join(format!("{name}.backup"))
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn write_backup(name: &str, snapshot: &WorldSnapshot) -> Result<(), BackupError> {
+    fs::create_dir_all(BACKUP_DIR)?;
+    let bytes = serde_pickle::to_vec(snapshot, serde_pickle::SerOptions::new())?;
+    fs::write(backup_path(name), bytes)?;
+    Ok(())
+}
+
+/// Snapshots `world` synchronously (so the backup is consistent with the moment the command was
+/// issued) and records it in [`World::backups`], then writes it to disk on a spawned Tokio task
+/// so the console command returns immediately.
+pub fn queue_backup(to_console: ToConsole, world: &mut World, name: String) {
+    let snapshot = WorldSnapshot::capture(world);
+    world.backups.push(BackupInfo {
+        name: name.clone(),
+        timestamp: now_unix(),
+    });
+
+    tokio::spawn(async move {
+        c_info!(to_console, "Backing up world to `{name}`...");
+        match write_backup(&name, &snapshot) {
+            Ok(_) => c_info!(to_console, "Backup `{name}` complete."),
+            Err(e) => c_error!(to_console, "Backup `{name}` failed: {e}"),
+        }
+    });
+}
+
+/// Loads the backup named `name`, replaces `world`'s chunks and spawn state with it, and
+/// re-broadcasts every loaded chunk to the players currently loading it so clients stay in sync.
+/// Also resets the position and inventory of any snapshotted player who's still connected, the
+/// same way a griefed or duped chunk gets rolled back - anyone not connected is skipped, since
+/// there's no live [`Player`] to reset.
+///
+/// [`Player`]: crate::player::Player
+pub async fn restore_backup(
+    to_console: ToConsole,
+    to_network: ToNetwork,
+    world: &mut World,
+    name: &str,
+) -> Result<(), BackupError> {
+    let bytes = fs::read(backup_path(name)).map_err(|_| BackupError::NotFound(name.to_string()))?;
+    let snapshot: WorldSnapshot = serde_pickle::from_slice(&bytes, serde_pickle::DeOptions::new())?;
+
+    world.chunks = snapshot.chunks;
+    world.spawn_point = snapshot.spawn_point;
+    if let Some(range) = NonZeroU32::new(snapshot.spawn_range) {
+        world.spawn_range = range;
+    }
+
+    let width_chunks = world.width / world.chunk_size;
+    let height_chunks = world.height / world.chunk_size;
+    for chunk_y in 0..height_chunks {
+        for chunk_x in 0..width_chunks {
+            let players_loading: Vec<_> = world
+                .get_list_of_players_loading_chunk(chunk_x, chunk_y)?
+                .into_iter()
+                .map(|conn| conn.addr)
+                .collect();
+            if players_loading.is_empty() {
+                continue;
+            }
+            let chunk = world.get_chunk(chunk_x, chunk_y)?.clone();
+            for addr in players_loading {
+                encode_and_send_reliable!(
+                    to_network,
+                    PacketTypes::ServerChunkResponse {
+                        chunk: chunk.clone().into(),
+                    },
+                    addr
+                );
+            }
+        }
+    }
+
+    for player_snapshot in &snapshot.players {
+        let Some(conn) = world
+            .players
+            .iter_mut()
+            .find(|conn| conn.name == player_snapshot.name)
+        else {
+            continue;
+        };
+
+        conn.server_player.x = player_snapshot.x;
+        conn.server_player.y = player_snapshot.y;
+        conn.server_player.inventory = player_snapshot.inventory.map(|stack_maybe| {
+            stack_maybe.map(|(id, count)| ItemStack {
+                item: Item::from(id),
+                count: NonZeroU8::new(count).unwrap_or_else(|| unreachable!()),
+                damage: 0,
+            })
+        });
+
+        conn.server_player
+            .notify_inventory_changed(to_network.clone(), conn.addr);
+        encode_and_send!(
+            to_network,
+            PacketTypes::ServerPlayerUpdatePos {
+                player_id: conn.id,
+                pos_x: conn.server_player.x,
+                pos_y: conn.server_player.y,
+            },
+            conn.addr
+        );
+    }
+
+    c_info!(to_console, "Restored backup `{name}`.");
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use crate::constants;
+use crate::network::ChatChannel;
+use crate::player::Item;
+use crate::plugins::PluginAction;
+use crate::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::IpAddr;
+use std::num::{NonZeroU8, ParseFloatError, ParseIntError};
+use std::path::{Path, PathBuf};
+use std::str::{FromStr, SplitWhitespace};
+use thiserror::Error;
+
+/// The shape of a single chat-command argument. Used to both parse the raw token and describe
+/// the command to callers - there's no free-form "rest of the line" schema on purpose, every
+/// built-in's arguments are fixed-arity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgSchema {
+    /// An opaque string token, taken as-is (e.g. an item name).
+    Literal,
+    /// A player's display name, resolved to their id at dispatch time.
+    PlayerName,
+    Integer,
+    Float,
+}
+
+/// A single argument, parsed according to its [`ArgSchema`].
+#[derive(Debug, Clone)]
+pub enum ArgValue {
+    Literal(String),
+    PlayerId(u32),
+    Integer(i64),
+    Float(f32),
+}
+
+impl ArgValue {
+    fn as_literal(&self) -> &str {
+        match self {
+            ArgValue::Literal(s) => s,
+            _ => unreachable!("schema/value mismatch"),
+        }
+    }
+    fn as_player_id(&self) -> u32 {
+        match self {
+            ArgValue::PlayerId(id) => *id,
+            _ => unreachable!("schema/value mismatch"),
+        }
+    }
+    fn as_integer(&self) -> i64 {
+        match self {
+            ArgValue::Integer(n) => *n,
+            _ => unreachable!("schema/value mismatch"),
+        }
+    }
+    fn as_float(&self) -> f32 {
+        match self {
+            ArgValue::Float(f) => *f,
+            _ => unreachable!("schema/value mismatch"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Unknown command `/{0}`")]
+    UnknownCommand(String),
+    #[error("Missing argument #{0}")]
+    MissingArgument(usize),
+    #[error("Cannot parse int: `{0}`")]
+    Int(#[from] ParseIntError),
+    #[error("Cannot parse float: `{0}`")]
+    Float(#[from] ParseFloatError),
+    #[error("No player named `{0}` is online")]
+    PlayerNotFound(String),
+    #[error("Unknown item `{0}`")]
+    UnknownItem(String),
+    #[error("Count must be between 1 and 255")]
+    CountOutOfRange,
+    #[error("You must be an operator to use this command")]
+    NotOperator,
+    #[error("`{0}` is not banned")]
+    NoSuchBan(String),
+    #[error("failed to persist ban list: {0}")]
+    Io(#[from] io::Error),
+    #[error("`/{0}` doesn't take {1} argument(s)")]
+    NoMatchingOverload(String, usize),
+}
+
+/// A single ban, keyed by player name and/or the IP they were last seen connecting from - either
+/// is enough to match, so a rejoin under a different name from the same address (or the same
+/// name from a new address) is still caught. Persisted as JSON, one array entry per ban.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BanEntry {
+    name: Option<String>,
+    ip: Option<IpAddr>,
+    reason: String,
+}
+
+/// A handler invoked once [`Commands::dispatch`] has checked the operator allowlist and parsed
+/// every argument. Like a plugin hook, it only ever gets a read-only [`World`] and returns the
+/// [`PluginAction`]s it wants applied - see [`crate::plugins::PluginAction`] for why.
+pub type CommandHandler = fn(&World, u32, &[ArgValue]) -> Result<Vec<PluginAction>, CommandError>;
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub args: &'static [ArgSchema],
+    /// Gates the command behind the operator allowlist loaded by [`Commands::load_operators`].
+    pub operator_only: bool,
+    pub handler: CommandHandler,
+}
+
+/// The chat command registry, dispatched from `/`-prefixed `ClientSendMessage`s. Built-ins are
+/// registered in [`Commands::new`]; call [`Commands::register`] to add more at runtime, the same
+/// way [`crate::plugins::Plugins`] hooks queue [`PluginAction`]s. Also owns the operator allowlist
+/// and ban list (`/ban`, `/pardon`), the latter consulted by `ClientHello` via [`Self::check_ban`].
+///
+/// A name can have more than one [`CommandSpec`] registered against it, as long as their `args`
+/// lengths differ - `/kill` does this to offer both a no-argument (kill yourself) and a
+/// `PlayerName` (kill someone else) form. [`Self::dispatch`] picks the overload whose arity
+/// matches the tokens actually typed; there's no need for a full argument-type tree on top since
+/// no built-in command is ambiguous by arity alone.
+pub struct Commands {
+    specs: HashMap<&'static str, Vec<CommandSpec>>,
+    operators: HashSet<String>,
+    bans: Vec<BanEntry>,
+    /// Where [`Self::load_bans`] read the ban list from, re-saved here every time `/ban`/`/pardon`
+    /// changes it. `None` if `load_bans` was never called (or its file didn't exist yet).
+    bans_path: Option<PathBuf>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        let mut commands = Commands {
+            specs: HashMap::new(),
+            operators: HashSet::new(),
+            bans: Vec::new(),
+            bans_path: None,
+        };
+        commands.register(CommandSpec {
+            name: "tp",
+            args: &[ArgSchema::Float, ArgSchema::Float],
+            operator_only: false,
+            handler: handle_tp,
+        });
+        commands.register(CommandSpec {
+            name: "kick",
+            args: &[ArgSchema::PlayerName],
+            operator_only: true,
+            handler: handle_kick,
+        });
+        commands.register(CommandSpec {
+            name: "give",
+            args: &[ArgSchema::Literal, ArgSchema::Integer],
+            operator_only: true,
+            handler: handle_give,
+        });
+        commands.register(CommandSpec {
+            name: "list",
+            args: &[],
+            operator_only: false,
+            handler: handle_list,
+        });
+        commands.register(CommandSpec {
+            name: "spawn",
+            args: &[],
+            operator_only: false,
+            handler: handle_spawn,
+        });
+        commands.register(CommandSpec {
+            name: "heal",
+            args: &[],
+            operator_only: false,
+            handler: handle_heal,
+        });
+        commands.register(CommandSpec {
+            name: "kill",
+            args: &[],
+            operator_only: false,
+            handler: handle_kill_self,
+        });
+        commands.register(CommandSpec {
+            name: "kill",
+            args: &[ArgSchema::PlayerName],
+            operator_only: true,
+            handler: handle_kill_other,
+        });
+        commands.register(CommandSpec {
+            name: "channel",
+            args: &[ArgSchema::Literal],
+            operator_only: false,
+            handler: handle_channel,
+        });
+        commands
+    }
+
+    /// Loads the operator allowlist from `path`, one player name per line (blank lines and
+    /// `#`-prefixed comments ignored). A missing file just means no operators, mirroring how
+    /// [`crate::plugins::Plugins::load`] treats a missing plugins directory.
+    pub fn load_operators(&mut self, path: &Path) -> io::Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        self.operators = std::fs::read_to_string(path)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        Ok(())
+    }
+
+    /// Loads the ban list from `path` (a JSON array of ban entries), remembering `path` so
+    /// `/ban`/`/pardon` can re-save it afterwards. A missing file just means no bans, mirroring
+    /// [`Self::load_operators`].
+    pub fn load_bans(&mut self, path: &Path) -> io::Result<()> {
+        self.bans_path = Some(path.to_path_buf());
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        self.bans = serde_json::from_str(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(())
+    }
+
+    /// Re-saves the ban list to wherever [`Self::load_bans`] read it from, if anywhere. Called
+    /// after every `/ban`/`/pardon` so a restart doesn't lose them.
+    fn save_bans(&self) -> io::Result<()> {
+        let Some(path) = &self.bans_path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_string_pretty(&self.bans)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, contents)
+    }
+
+    /// Returns the ban reason if `name` or `ip` matches an entry in the ban list, consulted from
+    /// `ClientHello` before a connection is allowed to spawn.
+    pub fn check_ban(&self, name: &str, ip: IpAddr) -> Option<&str> {
+        self.bans
+            .iter()
+            .find(|ban| ban.name.as_deref() == Some(name) || ban.ip == Some(ip))
+            .map(|ban| ban.reason.as_str())
+    }
+
+    /// Registers `spec` as an overload of its name, alongside any others already registered under
+    /// it - see the arity note on [`Commands`] itself.
+    pub fn register(&mut self, spec: CommandSpec) {
+        self.specs.entry(spec.name).or_default().push(spec);
+    }
+
+    /// Parses and dispatches a chat message's command line (everything after the leading `/`)
+    /// sent by `sender_id`/`sender_name`, returning the [`PluginAction`]s it queued. Callers
+    /// apply the result with [`crate::plugins::apply_actions`], same as a plugin hook's.
+    ///
+    /// `/ban` and `/pardon` are handled directly here rather than through the generic
+    /// [`CommandSpec`]/[`CommandHandler`] table, since (unlike every other built-in) they need to
+    /// mutate and persist `self.bans` - state a bare `fn` handler has no way to reach.
+    pub fn dispatch(
+        &mut self,
+        world: &World,
+        sender_id: u32,
+        sender_name: &str,
+        line: &str,
+    ) -> Result<Vec<PluginAction>, CommandError> {
+        let mut tokens = line.trim().split_whitespace();
+        let name = tokens.next().unwrap_or("");
+        match name {
+            "ban" => return self.handle_ban(world, sender_id, sender_name, tokens),
+            "pardon" => return self.handle_pardon(sender_id, sender_name, tokens),
+            _ => {}
+        }
+        let overloads = self
+            .specs
+            .get(name)
+            .ok_or_else(|| CommandError::UnknownCommand(name.to_string()))?;
+        let remaining: Vec<&str> = tokens.collect();
+        let spec = overloads
+            .iter()
+            .find(|spec| spec.args.len() == remaining.len())
+            .ok_or_else(|| CommandError::NoMatchingOverload(name.to_string(), remaining.len()))?;
+        if spec.operator_only && !self.operators.contains(sender_name) {
+            return Err(CommandError::NotOperator);
+        }
+        let mut args = Vec::with_capacity(spec.args.len());
+        for (schema, token) in spec.args.iter().zip(remaining.iter()) {
+            args.push(match schema {
+                ArgSchema::Literal => ArgValue::Literal(token.to_string()),
+                ArgSchema::PlayerName => {
+                    let id = world
+                        .players
+                        .iter()
+                        .find(|p| p.name == *token)
+                        .map(|p| p.id)
+                        .ok_or_else(|| CommandError::PlayerNotFound(token.to_string()))?;
+                    ArgValue::PlayerId(id)
+                }
+                ArgSchema::Integer => ArgValue::Integer(token.parse()?),
+                ArgSchema::Float => ArgValue::Float(token.parse()?),
+            });
+        }
+        (spec.handler)(world, sender_id, &args)
+    }
+
+    fn handle_ban(
+        &mut self,
+        world: &World,
+        sender_id: u32,
+        sender_name: &str,
+        mut tokens: SplitWhitespace,
+    ) -> Result<Vec<PluginAction>, CommandError> {
+        if !self.operators.contains(sender_name) {
+            return Err(CommandError::NotOperator);
+        }
+        let name = tokens.next().ok_or(CommandError::MissingArgument(0))?;
+        let online = world.players.iter().find(|p| p.name == name);
+        self.bans.push(BanEntry {
+            name: Some(name.to_string()),
+            ip: online.map(|p| p.addr.ip()),
+            reason: "Banned by an operator.".to_string(),
+        });
+        self.save_bans()?;
+        let mut actions = vec![PluginAction::SendChatTo {
+            player_id: sender_id,
+            msg: format!("Banned {name}."),
+        }];
+        if let Some(online) = online {
+            actions.push(PluginAction::Kick {
+                player_id: online.id,
+                msg: "You have been banned.".to_string(),
+            });
+        }
+        Ok(actions)
+    }
+
+    fn handle_pardon(
+        &mut self,
+        sender_id: u32,
+        sender_name: &str,
+        mut tokens: SplitWhitespace,
+    ) -> Result<Vec<PluginAction>, CommandError> {
+        if !self.operators.contains(sender_name) {
+            return Err(CommandError::NotOperator);
+        }
+        let name = tokens.next().ok_or(CommandError::MissingArgument(0))?;
+        let before = self.bans.len();
+        self.bans.retain(|ban| ban.name.as_deref() != Some(name));
+        if self.bans.len() == before {
+            return Err(CommandError::NoSuchBan(name.to_string()));
+        }
+        self.save_bans()?;
+        Ok(vec![PluginAction::SendChatTo {
+            player_id: sender_id,
+            msg: format!("Pardoned {name}."),
+        }])
+    }
+}
+
+impl Default for Commands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn handle_tp(_world: &World, sender_id: u32, args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    Ok(vec![PluginAction::Teleport {
+        player_id: sender_id,
+        x: args[0].as_float(),
+        y: args[1].as_float(),
+    }])
+}
+
+fn handle_kick(_world: &World, _sender_id: u32, args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    Ok(vec![PluginAction::Kick {
+        player_id: args[0].as_player_id(),
+        msg: "Kicked by an operator.".to_string(),
+    }])
+}
+
+fn handle_give(_world: &World, sender_id: u32, args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    let item_name = args[0].as_literal();
+    let item = Item::from_str(item_name).map_err(|_| CommandError::UnknownItem(item_name.to_string()))?;
+    let count = u8::try_from(args[1].as_integer())
+        .ok()
+        .and_then(NonZeroU8::new)
+        .ok_or(CommandError::CountOutOfRange)?;
+    Ok(vec![PluginAction::GiveItem {
+        player_id: sender_id,
+        item,
+        count,
+    }])
+}
+
+fn handle_list(world: &World, sender_id: u32, _args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    let names: Vec<&str> = world.players.iter().map(|p| p.name.as_str()).collect();
+    Ok(vec![PluginAction::SendChatTo {
+        player_id: sender_id,
+        msg: format!("{} players online: {}", names.len(), names.join(", ")),
+    }])
+}
+
+fn handle_spawn(_world: &World, sender_id: u32, _args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    Ok(vec![PluginAction::Respawn { player_id: sender_id }])
+}
+
+fn handle_heal(_world: &World, sender_id: u32, _args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    Ok(vec![PluginAction::SetHealth {
+        player_id: sender_id,
+        health: constants::MAX_HEALTH,
+    }])
+}
+
+fn handle_kill_self(_world: &World, sender_id: u32, _args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    Ok(vec![
+        PluginAction::SetHealth { player_id: sender_id, health: 0.0 },
+        PluginAction::Respawn { player_id: sender_id },
+    ])
+}
+
+fn handle_kill_other(_world: &World, _sender_id: u32, args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    let player_id = args[0].as_player_id();
+    Ok(vec![
+        PluginAction::SetHealth { player_id, health: 0.0 },
+        PluginAction::Respawn { player_id },
+    ])
+}
+
+/// `global` and `local` select the two built-in channels; anything else joins (or creates) a team
+/// channel of that name - see [`ChatChannel`].
+fn handle_channel(_world: &World, sender_id: u32, args: &[ArgValue]) -> Result<Vec<PluginAction>, CommandError> {
+    let channel = match args[0].as_literal() {
+        "global" => ChatChannel::Global,
+        "local" => ChatChannel::Local,
+        team => ChatChannel::Team(team.to_string()),
+    };
+    Ok(vec![PluginAction::SetChannel { player_id: sender_id, channel }])
+}
@@ -0,0 +1,66 @@
+use crate::world::{Block, World};
+use crate::{c_warn, console::ToConsole};
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The environment variable consulted for a config file path when `--config` isn't passed.
+const CONFIG_ENV_VAR: &str = "YOURCRAFT_CONFIG";
+
+/// Startup overrides loaded from a JSON file, layered on top of the `--world-*`/`--port` CLI
+/// flags (which already cover bind address, dimensions, chunk size and generation mode, and
+/// already let an operator run several instances from one binary without recompiling). The one
+/// thing those flags can't express is specific blocks to stamp down once the world is ready -
+/// a spawn platform, a landmark, a test fixture - so that's what this covers for now.
+#[derive(Debug, Deserialize, Default)]
+pub struct ServerConfig {
+    /// Blocks placed once, after the world finishes generating or loading and before the server
+    /// starts accepting players.
+    #[serde(default)]
+    pub initial_blocks: Vec<InitialBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InitialBlock {
+    pub x: u32,
+    pub y: u32,
+    pub block: Block,
+}
+
+/// Resolves the config file path: `explicit` (from `--config`) if given, else [`CONFIG_ENV_VAR`],
+/// else none.
+fn resolve_path(explicit: Option<&Path>) -> Option<PathBuf> {
+    explicit
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(CONFIG_ENV_VAR).map(PathBuf::from))
+}
+
+/// Loads a [`ServerConfig`] from `explicit` or [`CONFIG_ENV_VAR`]. A missing path (in either
+/// source) or a missing file just means no overrides, mirroring how
+/// [`crate::chat_commands::Commands::load_operators`] treats a missing operators file.
+pub fn load(explicit: Option<&Path>) -> io::Result<ServerConfig> {
+    let Some(path) = resolve_path(explicit) else {
+        return Ok(ServerConfig::default());
+    };
+    if !path.exists() {
+        return Ok(ServerConfig::default());
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Applies `config`'s initial block placements to `world`. A placement out of bounds is logged
+/// and skipped rather than failing startup - the rest of the config (and the world itself) is
+/// still good.
+pub fn apply(config: &ServerConfig, world: &mut World, to_console: &ToConsole) {
+    for placement in &config.initial_blocks {
+        if let Err(e) = world.set_block(placement.x, placement.y, placement.block) {
+            c_warn!(
+                to_console,
+                "config: couldn't place initial block at ({}, {}): {e}",
+                placement.x,
+                placement.y
+            );
+        }
+    }
+}
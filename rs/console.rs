@@ -9,9 +9,10 @@ use ratatui::{
     DefaultTerminal, Frame,
 };
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
 use std::{
     io,
-    num::{NonZeroU32, ParseFloatError, ParseIntError},
+    num::{NonZeroU32, NonZeroU8, ParseFloatError, ParseIntError},
     str::FromStr,
 };
 use thiserror::Error;
@@ -24,8 +25,10 @@ use tui_textarea::{Input, Key, TextArea};
 
 use crate::{
     constants,
+    nat::PublicAddr,
     network::ToNetwork,
-    player::{Acceleration, Player, Velocity},
+    player::{Acceleration, Item, ItemStack, Player, Velocity},
+    web::ToWeb,
     world::{self, BlockPos, World},
 };
 use tokio::time::Duration;
@@ -45,9 +48,27 @@ pub enum Command {
     Respawn(u32),
     SetBlock { pos: BlockPos },
     GetBlock { x: u32, y: u32 },
+    Fill { from: (u32, u32), to: (u32, u32), block: world::Block },
+    Clone { src_from: (u32, u32), src_to: (u32, u32), dest: (u32, u32) },
     SetSpawn(u32),
     SetSpawnRange(NonZeroU32),
     InventorySee(u32),
+    Give { id: u32, item: Item, count: NonZeroU8 },
+    ClearInventory(u32),
+    SetSlot { id: u32, slot: u8, item: Item, count: NonZeroU8 },
+    CreateDetachedInventory(String),
+    BindDetachedInventory { name: String, id: u32 },
+    UnbindDetachedInventory(String),
+    GiveDetached { name: String, item: Item, count: NonZeroU8 },
+    Trade { from_id: u32, to_id: u32 },
+    TradeOffer { id: u32, slot: u8 },
+    TradeConfirm(u32),
+    TradeCancel(u32),
+    Backup(String),
+    Restore(String),
+    ListBackups,
+    ItemInfo(Item),
+    PublicAddr,
 }
 
 #[derive(Error, Debug)]
@@ -74,6 +95,8 @@ pub enum ArgParseError {
     Float(#[from] ParseFloatError),
     #[error("Cannot parse Block: `{0}`")]
     Block(#[from] strum::ParseError),
+    #[error("Cannot parse Item: `{0}`")]
+    Item(strum::ParseError),
 }
 
 macro_rules! next_type_token_or_err {
@@ -108,6 +131,7 @@ impl FromStr for Command {
             "mspt" => Ok(Command::Mspt),
             "tps" => Ok(Command::Tps),
             "players" | "p" => Ok(Command::Players),
+            "publicaddr" | "pubaddr" => Ok(Command::PublicAddr),
             "kick" => {
                 let player_id = next_type_token_or_err!(tokens, "player_id", u32);
                 let reason = next_token!(tokens, "reason");
@@ -140,6 +164,36 @@ impl FromStr for Command {
 
                 Ok(Command::SetBlock { pos: (x, y, block) })
             }
+            "fill" => {
+                let x1 = next_type_token_or_err!(tokens, "x1", u32);
+                let y1 = next_type_token_or_err!(tokens, "y1", u32);
+                let x2 = next_type_token_or_err!(tokens, "x2", u32);
+                let y2 = next_type_token_or_err!(tokens, "y2", u32);
+                let block = world::Block::from_str(next_token!(tokens, "block")).map_err(|e| {
+                    CommandError::ArgParseError {
+                        arg: "block".to_string(),
+                        err: ArgParseError::Block(e),
+                    }
+                })?;
+                Ok(Command::Fill {
+                    from: (x1, y1),
+                    to: (x2, y2),
+                    block,
+                })
+            }
+            "clone" => {
+                let x1 = next_type_token_or_err!(tokens, "src_x1", u32);
+                let y1 = next_type_token_or_err!(tokens, "src_y1", u32);
+                let x2 = next_type_token_or_err!(tokens, "src_x2", u32);
+                let y2 = next_type_token_or_err!(tokens, "src_y2", u32);
+                let dest_x = next_type_token_or_err!(tokens, "dest_x", u32);
+                let dest_y = next_type_token_or_err!(tokens, "dest_y", u32);
+                Ok(Command::Clone {
+                    src_from: (x1, y1),
+                    src_to: (x2, y2),
+                    dest: (dest_x, dest_y),
+                })
+            }
             "spawn" => {
                 let x = next_type_token_or_err!(tokens, "x", u32);
                 Ok(Command::SetSpawn(x))
@@ -156,11 +210,128 @@ impl FromStr for Command {
                 let id = next_type_token_or_err!(tokens, "player_id", u32);
                 Ok(Command::InventorySee(id))
             }
+            "give" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                let item = Item::from_str(next_token!(tokens, "item")).map_err(|e| {
+                    CommandError::ArgParseError {
+                        arg: "item".to_string(),
+                        err: ArgParseError::Item(e),
+                    }
+                })?;
+                let count = next_type_token_or_err!(tokens, "count", NonZeroU8);
+                Ok(Command::Give { id, item, count })
+            }
+            "clearinventory" | "clearinv" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                Ok(Command::ClearInventory(id))
+            }
+            "setslot" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                let slot = next_type_token_or_err!(tokens, "slot", u8);
+                let item = Item::from_str(next_token!(tokens, "item")).map_err(|e| {
+                    CommandError::ArgParseError {
+                        arg: "item".to_string(),
+                        err: ArgParseError::Item(e),
+                    }
+                })?;
+                let count = next_type_token_or_err!(tokens, "count", NonZeroU8);
+                Ok(Command::SetSlot {
+                    id,
+                    slot,
+                    item,
+                    count,
+                })
+            }
+            "createdetachedinventory" => {
+                let name = next_token!(tokens, "name");
+                Ok(Command::CreateDetachedInventory(name.to_string()))
+            }
+            "binddetachedinventory" => {
+                let name = next_token!(tokens, "name").to_string();
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                Ok(Command::BindDetachedInventory { name, id })
+            }
+            "unbinddetachedinventory" => {
+                let name = next_token!(tokens, "name");
+                Ok(Command::UnbindDetachedInventory(name.to_string()))
+            }
+            "givedetached" => {
+                let name = next_token!(tokens, "name").to_string();
+                let item = Item::from_str(next_token!(tokens, "item")).map_err(|e| {
+                    CommandError::ArgParseError {
+                        arg: "item".to_string(),
+                        err: ArgParseError::Item(e),
+                    }
+                })?;
+                let count = next_type_token_or_err!(tokens, "count", NonZeroU8);
+                Ok(Command::GiveDetached { name, item, count })
+            }
+            "trade" => {
+                let from_id = next_type_token_or_err!(tokens, "from_id", u32);
+                let to_id = next_type_token_or_err!(tokens, "to_id", u32);
+                Ok(Command::Trade { from_id, to_id })
+            }
+            "tradeoffer" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                let slot = next_type_token_or_err!(tokens, "slot", u8);
+                Ok(Command::TradeOffer { id, slot })
+            }
+            "tradeconfirm" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                Ok(Command::TradeConfirm(id))
+            }
+            "tradecancel" => {
+                let id = next_type_token_or_err!(tokens, "player_id", u32);
+                Ok(Command::TradeCancel(id))
+            }
+            "backup" => {
+                let name = next_token!(tokens, "name");
+                Ok(Command::Backup(name.to_string()))
+            }
+            "restore" => {
+                let name = next_token!(tokens, "name");
+                Ok(Command::Restore(name.to_string()))
+            }
+            "backups" | "listbackups" => Ok(Command::ListBackups),
+            "iteminfo" => {
+                let item = Item::from_str(next_token!(tokens, "item")).map_err(|e| {
+                    CommandError::ArgParseError {
+                        arg: "item".to_string(),
+                        err: ArgParseError::Item(e),
+                    }
+                })?;
+                Ok(Command::ItemInfo(item))
+            }
             c => Err(CommandError::InvalidCommand(c.to_string())),
         }
     }
 }
 
+/// Every literal command word [`Command::from_str`] recognizes, aliases included (`h`/`?` for
+/// `help`, `tp` for `teleport`, ...) - a user typing either an alias or the canonical form should
+/// see it suggested. Used by [`suggest`].
+const COMMAND_LITERALS: &[&str] = &[
+    "help", "h", "?", "exit", "stop", "mspt", "tps", "players", "p", "publicaddr", "pubaddr",
+    "kick", "respawn", "teleport", "tp", "get", "block_at", "set", "fill", "clone", "spawn",
+    "spawn_range", "invsee", "inventorysee", "give", "clearinventory", "clearinv", "setslot",
+    "createdetachedinventory", "binddetachedinventory", "unbinddetachedinventory", "givedetached",
+    "trade", "tradeoffer", "tradeconfirm", "tradecancel", "backup", "restore", "backups",
+    "listbackups", "iteminfo",
+];
+
+/// Command-word completions for whatever's typed so far as the console's first token - e.g.
+/// `suggest("te")` returns `["teleport"]`. Only completes the command word itself, not its
+/// arguments; wiring this into a Tab keypress is left for later, since `Tab` already toggles
+/// autoscroll in `RatatuiConsole::process_terminal_events` and picking a new binding for it isn't
+/// this change's call to make.
+pub fn suggest(prefix: &str) -> Vec<&'static str> {
+    COMMAND_LITERALS
+        .iter()
+        .copied()
+        .filter(|literal| literal.starts_with(prefix))
+        .collect()
+}
+
 pub enum LogLevel {
     Debug,
     Info,
@@ -168,7 +339,7 @@ pub enum LogLevel {
     Error,
 }
 pub struct Log(pub LogLevel, pub String);
-#[derive(Default)]
+#[derive(Default, Clone, Serialize)]
 pub struct Stats {
     pub uptime: Duration,
     pub tps: u128,
@@ -221,8 +392,14 @@ macro_rules! c_error {
     };
 }
 
-pub fn init(console_enabled: bool, debug: bool) -> (JoinHandle<()>, FromConsole, ToConsole) {
+pub fn init(
+    console_enabled: bool,
+    debug: bool,
+) -> (JoinHandle<()>, FromConsole, ToConsole, UnboundedSender<Command>) {
     let (to_main, from_console) = mpsc::unbounded_channel::<Command>();
+    // handed out to other command sources (e.g. the admin web dashboard) so they can submit
+    // commands through the same path as the TTY console
+    let external_to_main = to_main.clone();
     // if console_enabled is false, simply keep the channel open but don't send messages
     let (to_console, from_main) = mpsc::unbounded_channel::<ToConsoleType>();
     let console_thread = tokio::spawn(async move {
@@ -253,7 +430,7 @@ pub fn init(console_enabled: bool, debug: bool) -> (JoinHandle<()>, FromConsole,
             }
         }
     });
-    (console_thread, from_console, to_console)
+    (console_thread, from_console, to_console, external_to_main)
 }
 
 struct RatatuiConsole<'a> {
@@ -545,7 +722,9 @@ impl RatatuiConsole<'_> {
 
 pub async fn process_command(
     to_console: ToConsole,
+    to_web: ToWeb,
     to_network: ToNetwork,
+    public_addr: &PublicAddr,
     world: &mut World,
     command: Command,
     tick_times_saved: [Duration; 8],
@@ -554,7 +733,7 @@ pub async fn process_command(
 ) -> io::Result<bool> {
     match command {
         Command::Help => {
-            c_info!(to_console, "Commands: help/h/?, exit/stop, tps, mspt, players/p, respawn (player_id), kick (player_id), teleport/tp (player_id, x, y) block_at/get (x, y), set (x, y, Block), spawn (x), spawn_range (range), inventorysee/invsee (player_id)");
+            c_info!(to_console, "Commands: help/h/?, exit/stop, tps, mspt, players/p, publicaddr/pubaddr, respawn (player_id), kick (player_id), teleport/tp (player_id, x, y) block_at/get (x, y), set (x, y, Block), fill (x1, y1, x2, y2, Block), clone (src_x1, src_y1, src_x2, src_y2, dest_x, dest_y), spawn (x), spawn_range (range), inventorysee/invsee (player_id), give (player_id, Item, count), clearinventory/clearinv (player_id), setslot (player_id, slot, Item, count), createdetachedinventory (name), binddetachedinventory (name, player_id), unbinddetachedinventory (name), givedetached (name, Item, count), trade (from_id, to_id), tradeoffer (player_id, slot), tradeconfirm (player_id), tradecancel (player_id), backup (name), restore (name), backups/listbackups, iteminfo (Item)");
         }
         Command::Shutdown => {
             return Ok(true);
@@ -633,8 +812,14 @@ pub async fn process_command(
                 );
             });
         }
+        Command::PublicAddr => match *public_addr.borrow() {
+            Some(addr) => c_info!(to_console, "Public address: {addr}"),
+            None => c_warn!(to_console, "Public address not yet discovered (is --nat-traversal set?)"),
+        },
         Command::Kick(id, msg) => {
-            world.kick(to_console, to_network, id, Some(&msg)).await?;
+            world
+                .kick(to_console, to_web, to_network, id, Some(&msg))
+                .await?;
         }
         Command::Respawn(id) => {
             let idx_maybe = world.players.par_iter().position_any(|conn| conn.id == id);
@@ -679,11 +864,35 @@ pub async fn process_command(
         }
         Command::SetBlock { pos } => {
             let (x, y, block) = pos;
-            match world.set_block_and_notify(to_network, x, y, block).await {
+            match world
+                .set_block_and_notify(to_web, to_network, x, y, block)
+                .await
+            {
                 Ok(_) => c_info!(to_console, "Set block at ({x}, {y}) to {block:?}"),
                 Err(e) => c_error!(to_console, "Cannot set block at ({x}, {y}): {e}"),
             };
         }
+        Command::Fill { from, to, block } => match world.fill_region(from, to, block) {
+            Ok(count) => c_info!(
+                to_console,
+                "Filled {count} blocks from {from:?} to {to:?} with {block:?}"
+            ),
+            Err(e) => c_error!(to_console, "Cannot fill {from:?} to {to:?}: {e}"),
+        },
+        Command::Clone {
+            src_from,
+            src_to,
+            dest,
+        } => match world.clone_region(src_from, src_to, dest) {
+            Ok(count) => c_info!(
+                to_console,
+                "Cloned {count} blocks from {src_from:?}-{src_to:?} to {dest:?}"
+            ),
+            Err(e) => c_error!(
+                to_console,
+                "Cannot clone {src_from:?}-{src_to:?} to {dest:?}: {e}"
+            ),
+        },
         Command::GetBlock { x, y } => {
             match world.get_block(x, y) {
                 Ok(bl) => c_info!(to_console, "{bl:?} at ({x}, {y})"),
@@ -717,6 +926,157 @@ pub async fn process_command(
                 None => c_error!(to_console, "Player doesn't exist."),
             }
         }
+        Command::Give { id, item, count } => {
+            match world.players.par_iter().position_any(|conn| conn.id == id) {
+                Some(idx) => {
+                    let result = world.players[idx]
+                        .server_player
+                        .insert(ItemStack { item, count, damage: 0 });
+                    world.players[idx]
+                        .server_player
+                        .notify_inventory_changed(to_network, world.players[idx].addr);
+                    match result {
+                        Ok(_) => c_info!(
+                            to_console,
+                            "Gave {count} {item:?} to {} (id {id})",
+                            world.players[idx].name
+                        ),
+                        Err(remaining) => c_error!(
+                            to_console,
+                            "Inventory full, {remaining} {item:?} could not fit"
+                        ),
+                    }
+                }
+                None => c_error!(to_console, "Player {id} does not exist!"),
+            }
+        }
+        Command::ClearInventory(id) => {
+            match world.players.par_iter().position_any(|conn| conn.id == id) {
+                Some(idx) => {
+                    world.players[idx].server_player.inventory = [None; 9];
+                    world.players[idx]
+                        .server_player
+                        .notify_inventory_changed(to_network, world.players[idx].addr);
+                    c_info!(
+                        to_console,
+                        "Cleared inventory of {} (id {id})",
+                        world.players[idx].name
+                    );
+                }
+                None => c_error!(to_console, "Player {id} does not exist!"),
+            }
+        }
+        Command::SetSlot {
+            id,
+            slot,
+            item,
+            count,
+        } => match world.players.par_iter().position_any(|conn| conn.id == id) {
+            Some(idx) => {
+                if (slot as usize) >= world.players[idx].server_player.inventory.len() {
+                    c_error!(to_console, "Slot {slot} out of range (0-8)");
+                } else {
+                    world.players[idx].server_player.inventory[slot as usize] =
+                        Some(ItemStack { item, count, damage: 0 });
+                    world.players[idx]
+                        .server_player
+                        .notify_inventory_changed(to_network, world.players[idx].addr);
+                    c_info!(
+                        to_console,
+                        "Set slot {slot} of {} (id {id}) to {count} {item:?}",
+                        world.players[idx].name
+                    );
+                }
+            }
+            None => c_error!(to_console, "Player {id} does not exist!"),
+        },
+        Command::CreateDetachedInventory(name) => {
+            world.create_detached_inventory(name.clone());
+            c_info!(to_console, "Created detached inventory `{name}`");
+        }
+        Command::BindDetachedInventory { name, id } => {
+            match world.bind_detached_inventory(&name, Some(id)) {
+                Ok(_) => c_info!(to_console, "Bound detached inventory `{name}` to player {id}"),
+                Err(e) => c_error!(to_console, "Cannot bind `{name}`: {e}"),
+            }
+        }
+        Command::UnbindDetachedInventory(name) => match world.bind_detached_inventory(&name, None)
+        {
+            Ok(_) => c_info!(to_console, "Unbound detached inventory `{name}`"),
+            Err(e) => c_error!(to_console, "Cannot unbind `{name}`: {e}"),
+        },
+        Command::GiveDetached { name, item, count } => {
+            match world.give_detached(to_network, &name, item, count) {
+                Ok(Ok(_)) => c_info!(to_console, "Gave {count} {item:?} to `{name}`"),
+                Ok(Err(remaining)) => c_error!(
+                    to_console,
+                    "Detached inventory `{name}` full, {remaining} {item:?} could not fit"
+                ),
+                Err(e) => c_error!(to_console, "Cannot give to `{name}`: {e}"),
+            }
+        }
+        Command::Trade { from_id, to_id } => match crate::trade::open_trade(world, from_id, to_id)
+        {
+            Ok(_) => c_info!(to_console, "Opened trade between {from_id} and {to_id}"),
+            Err(e) => c_error!(to_console, "Cannot open trade: {e}"),
+        },
+        Command::TradeOffer { id, slot } => match crate::trade::offer_slot(world, id, slot) {
+            Ok(_) => c_info!(to_console, "Player {id} staged slot {slot} for trade"),
+            Err(e) => c_error!(to_console, "Cannot stage slot {slot} for player {id}: {e}"),
+        },
+        Command::TradeConfirm(id) => {
+            match crate::trade::confirm(world, to_network, id).await {
+                Ok(true) => c_info!(to_console, "Trade involving player {id} completed"),
+                Ok(false) => c_info!(to_console, "Player {id} confirmed, waiting on the other side"),
+                Err(e) => c_error!(to_console, "Cannot confirm trade for player {id}: {e}"),
+            }
+        }
+        Command::TradeCancel(id) => match crate::trade::cancel(world, id) {
+            Ok(_) => c_info!(to_console, "Cancelled trade involving player {id}"),
+            Err(e) => c_error!(to_console, "Cannot cancel trade for player {id}: {e}"),
+        },
+        Command::Backup(name) => {
+            crate::backups::queue_backup(to_console, world, name);
+        }
+        Command::Restore(name) => {
+            if let Err(e) =
+                crate::backups::restore_backup(to_console.clone(), to_network, world, &name).await
+            {
+                c_error!(to_console, "Cannot restore backup `{name}`: {e}");
+            }
+        }
+        Command::ListBackups => {
+            if world.backups.is_empty() {
+                c_info!(to_console, "No backups taken yet.");
+            } else {
+                c_info!(to_console, "Available backups:");
+                world.backups.iter().for_each(|backup| {
+                    c_info!(to_console, "  {} (taken at {})", backup.name, backup.timestamp);
+                });
+            }
+        }
+        Command::ItemInfo(item) => {
+            let info = item.info();
+            let places = info
+                .places
+                .map(|block| format!("{block:?}"))
+                .unwrap_or_else(|| "nothing".to_string());
+            let can_mine = if info.can_mine.is_empty() {
+                "nothing".to_string()
+            } else {
+                info.can_mine
+                    .iter()
+                    .map(|block| format!("{block:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            c_info!(
+                to_console,
+                "{item:?} (id {}): places {places}, breaking power {}, can mine: {can_mine}",
+                info.id,
+                info.breaking_power
+            );
+        }
     }
     Ok(false)
 }
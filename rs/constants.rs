@@ -11,10 +11,26 @@ pub const SECONDS_BETWEEN_HEARTBEATS: u64 = 10;
 pub const G: f32 = 9.81 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
 pub const KNOCKBACK_POWER: f32 = 50.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
 pub const AIR_RESISTANCE: f32 = 40.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
+/// Water's drag, much higher than air's so falls and swims both slow down fast.
+pub const WATER_RESISTANCE: f32 = 120.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
+/// Lava's drag, higher still - wading through it should feel like wading through syrup.
+pub const LAVA_RESISTANCE: f32 = 300.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
 /// unit: units / tick (20ms)
 pub const TERMINAL_VELOCITY: f32 = 54.0 / (PHYS_TICKS_PER_SECOND as f32);
+/// Buoyancy's cap on sink speed in water, well below `TERMINAL_VELOCITY`.
+pub const WATER_TERMINAL_VELOCITY: f32 = 10.0 / (PHYS_TICKS_PER_SECOND as f32);
 pub const INITIAL_JUMP_SPEED: f32 = 25.0 / (PHYS_TICKS_PER_SECOND as f32);
 pub const INITIAL_JUMP_ACCEL: f32 = 50.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
+/// Sustained upward push while holding jump underwater, in place of a one-shot jump impulse.
+pub const SWIM_ACCEL: f32 = 40.0 / (PHYS_TICKS_PER_SECOND.pow(2) as f32);
+/// Fixed vertical speed while climbing a ladder.
+pub const CLIMB_SPEED: f32 = 15.0 / (PHYS_TICKS_PER_SECOND as f32);
+/// Health lost per physics tick while standing in lava (works out to 1 health/second).
+pub const LAVA_DAMAGE_PER_TICK: f32 = 1.0 / (PHYS_TICKS_PER_SECOND as f32);
 pub const RESPAWN_THRESHOLD: f32 = -256.0;
+/// A freshly spawned player's health, and the ceiling `/heal` restores up to.
+pub const MAX_HEALTH: f32 = 5.0;
 pub const MAX_INTERACT_RANGE: u32 = 10;
 pub const PACKET_BATCH_THRESHOLD: usize = 5;
+/// The longest a player's display name is allowed to be.
+pub const MAX_PLAYER_NAME_LENGTH: usize = 16;
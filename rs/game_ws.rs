@@ -0,0 +1,131 @@
+use crate::console::ToConsole;
+use crate::network::{IncomingEvent, PacketTypes, ToMain, WsClients};
+use crate::{c_error, c_info, c_warn};
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Error, Debug)]
+pub enum GameWsError {
+    #[error("error binding game websocket port: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Starts a WebSocket listener on `bind_addr` for browser-based game clients, speaking the exact
+/// same [`PacketTypes`] protocol as the UDP listener (see [`crate::network::init`]) and sharing
+/// the same `World` through the same `to_main` queue `main.rs` already drains `FromNetwork` from.
+/// A connected client's TCP peer address doubles as its `SocketAddr` everywhere else in the
+/// server - `process_client_packet` can't tell it apart from a UDP client - and outgoing packets
+/// addressed to it are intercepted via `ws_clients` and written here instead of onto the UDP
+/// socket (see [`WsClients`]).
+///
+/// Unlike UDP, a WebSocket connection is already ordered and reliable (TCP), so messages here
+/// skip `network`'s ack/fragment framing entirely: each binary WebSocket message carries exactly
+/// one encoded [`PacketTypes`], same as what [`PacketTypes::to_bytes`] produces.
+pub async fn init(
+    bind_addr: SocketAddr,
+    to_console: ToConsole,
+    to_main: ToMain,
+    ws_clients: WsClients,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>, GameWsError> {
+    let listener = TcpListener::bind(bind_addr).await?;
+    c_info!(to_console, "Game websocket listening on {bind_addr}");
+    Ok(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => {
+                    c_info!(to_console, "Game websocket shutting down");
+                    break;
+                }
+                accepted = listener.accept() => {
+                    let (stream, addr) = match accepted {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            c_error!(to_console, "error accepting game websocket connection: {e}");
+                            continue;
+                        }
+                    };
+                    tokio::spawn(handle_connection(
+                        stream,
+                        addr,
+                        to_console.clone(),
+                        to_main.clone(),
+                        ws_clients.clone(),
+                    ));
+                }
+            }
+        }
+    }))
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    to_console: ToConsole,
+    to_main: ToMain,
+    ws_clients: WsClients,
+) {
+    let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws_stream) => ws_stream,
+        Err(e) => {
+            c_error!(to_console, "game websocket handshake with {addr} failed: {e}");
+            return;
+        }
+    };
+    c_info!(to_console, "game client connected from {addr} over websocket");
+
+    let (to_client, mut from_server) = mpsc::unbounded_channel::<Vec<u8>>();
+    ws_clients.lock().unwrap().insert(addr, to_client);
+
+    let (mut write, mut read) = ws_stream.split();
+    loop {
+        tokio::select! {
+            outgoing = from_server.recv() => {
+                match outgoing {
+                    Some(bytes) => {
+                        if write.send(Message::Binary(bytes)).await.is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            incoming = read.next() => {
+                match incoming {
+                    Some(Ok(Message::Binary(bytes))) => {
+                        match PacketTypes::from_bytes(&bytes) {
+                            Ok(packet) => {
+                                let _ = to_main.send((addr, IncomingEvent::Packet(packet)));
+                            }
+                            Err(e) => {
+                                c_warn!(
+                                    to_console,
+                                    "Recieved unknown packet from {} over websocket, ignoring! (Err: {:?})",
+                                    addr,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        c_error!(to_console, "game websocket {addr} connection error: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    ws_clients.lock().unwrap().remove(&addr);
+    // Same signal a UDP client's exhausted ack retries send - lets main.rs kick whatever player
+    // this connection was, exactly like a timed-out UDP peer.
+    let _ = to_main.send((addr, IncomingEvent::PeerTimedOut));
+    c_info!(to_console, "game client {addr} disconnected from websocket");
+}
@@ -2,20 +2,37 @@ use crate::world::World;
 use clap::{Parser, Subcommand};
 use console::Stats;
 use log::{error, info, LevelFilter};
+use rand::RngCore;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 use std::cmp::max;
 use std::io;
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 use std::process::exit;
+use std::sync::Arc;
 use tokio::net::UdpSocket;
+use tokio::sync::watch;
 use tokio::time::{self, Duration};
 
 #[macro_use]
 mod network;
+mod backups;
+mod chat_commands;
+mod config;
 mod console;
 mod constants;
+mod game_ws;
+mod masterlist;
+mod metrics;
+mod nat;
 mod player;
+mod plugins;
+mod tasks;
+mod trade;
+mod updates;
+mod web;
 mod world;
+mod worldfile;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about)]
@@ -51,6 +68,72 @@ struct Settings {
     /// The amount of network errors that are allowed to happen before the server exits.
     #[arg(long, default_value = "3")]
     max_network_errors: u8,
+    /// The master server URL to heartbeat this server's listing to. If unset, the server won't
+    /// register with any public server list.
+    #[arg(long)]
+    heartbeat_url: Option<String>,
+    /// The name this server advertises on the public server list.
+    #[arg(long, default_value = "A yourcraft Server")]
+    server_name: String,
+    /// Whether to advertise this server as public on the master server list.
+    #[arg(long, default_value = "false")]
+    public: bool,
+    /// The maximum player count advertised to the master server list.
+    #[arg(long, default_value = "20")]
+    max_players: u32,
+    /// How often, in seconds, to send a heartbeat to `--heartbeat-url`. Has no effect if
+    /// `--heartbeat-url` isn't set.
+    #[arg(long, default_value = "45")]
+    masterlist_interval: u64,
+    /// A file to load the world from on startup (if it exists) and save it to on shutdown and
+    /// autosave. If unset, the world is never persisted to disk.
+    #[arg(long)]
+    world_file: Option<PathBuf>,
+    /// How often, in seconds, to autosave the world to `--world-file`. Has no effect if
+    /// `--world-file` isn't set.
+    #[arg(long, default_value = "300")]
+    autosave_interval: u64,
+    /// Enables the admin WebSocket dashboard on this port, broadcasting live stats and events and
+    /// accepting console commands from connected browsers. If unset, the dashboard is disabled.
+    #[arg(long)]
+    web_port: Option<u16>,
+    /// Enables a WebSocket endpoint on this port for browser-based game clients, speaking the same
+    /// packet protocol as the UDP listener and sharing the same world and players. If unset, only
+    /// UDP clients can connect.
+    #[arg(long)]
+    ws_port: Option<u16>,
+    /// Enables a Prometheus `/metrics` endpoint on this port, exposing packet, block, and player
+    /// counters for scraping. If unset, no metrics endpoint is started.
+    #[arg(long)]
+    metrics_port: Option<u16>,
+    /// Enables automatic NAT traversal: discovers this server's public endpoint via STUN and
+    /// asks the router for a UPnP-IGD forward of `--port`, refreshing the lease on an interval.
+    /// Never fatal if no STUN reflector or IGD router responds - a warning is logged and the
+    /// server keeps running, just possibly unreachable from outside the LAN.
+    #[arg(long, default_value = "false")]
+    nat_traversal: bool,
+    /// The STUN server to query for public address discovery when `--nat-traversal` is set.
+    #[arg(long, default_value = "stun.l.google.com:19302")]
+    stun_server: String,
+    /// The directory to load `.lua` plugins from on startup. Plugins can hook player joins,
+    /// block placement/breaking, chat, and ticks.
+    #[arg(long, default_value = "plugins")]
+    plugins_dir: PathBuf,
+    /// A file listing, one per line, the player names allowed to run destructive chat commands
+    /// (`/kick`, `/give`). Blank lines and `#`-prefixed comments are ignored. If unset or
+    /// missing, no player can run them.
+    #[arg(long, default_value = "operators.txt")]
+    operators_file: PathBuf,
+    /// A JSON file listing banned players, mutable at runtime with `/ban` and `/pardon`. If unset
+    /// or missing, no player is banned.
+    #[arg(long, default_value = "bans.json")]
+    bans_file: PathBuf,
+    /// A JSON config file for startup overrides that don't have their own flag yet (currently
+    /// just a list of blocks to place once the world is ready). Falls back to the
+    /// `YOURCRAFT_CONFIG` environment variable if unset, and to no overrides if neither is set
+    /// or the file doesn't exist.
+    #[arg(long)]
+    config: Option<PathBuf>,
     /// The world type to generate.
     #[command(subcommand)]
     world_type: WorldType,
@@ -94,9 +177,32 @@ pub enum WorldType {
         /// The radius used for the Poisson disk distribution for tree geneartion.
         #[arg(long, default_value = "5.0")]
         tree_spawn_radius: f64,
+        /// The frequency of the low-frequency noise pass that selects biomes (Plains, Desert,
+        /// Mountains, Forest) per column. Smaller means wider, more gradual biome bands.
+        #[arg(long, default_value = "0.001")]
+        biome_scale: f64,
     },
 }
 
+/// Resolves alongside `ctrl_c()` on Unix so process managers (systemd, Docker) that stop
+/// services with `SIGTERM` trigger the same graceful shutdown - world save and all - as a local
+/// Ctrl+C. Never resolves on other platforms, where `SIGTERM` doesn't exist.
+#[cfg(unix)]
+async fn wait_for_terminate() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut term) => {
+            term.recv().await;
+        }
+        Err(_) => std::future::pending().await,
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_terminate() {
+    std::future::pending().await
+}
+
 #[tokio::main]
 async fn main() -> io::Result<()> {
     let settings = Settings::parse();
@@ -111,45 +217,85 @@ async fn main() -> io::Result<()> {
         })
         .init();
 
-    let (console_thread, mut from_console, to_console) =
+    let (console_thread, mut from_console, to_console, to_main) =
         console::init(!settings.no_console, settings.debug);
+    let mut tasks = tasks::TaskRunner::new();
+    tasks.register("console", console_thread);
+
+    let metrics = match metrics::Metrics::new() {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            let _ = to_console.send(console::ToConsoleType::Quit);
+            tasks.shutdown(to_console.clone()).await;
+            error!("Error registering metrics: {e}");
+            exit(1);
+        }
+    };
+    if let Some(metrics_port) = settings.metrics_port {
+        match metrics::init(([0, 0, 0, 0], metrics_port).into(), to_console.clone(), metrics.clone()).await {
+            Ok(handle) => tasks.register("metrics endpoint", handle),
+            Err(e) => {
+                let _ = to_console.send(console::ToConsoleType::Quit);
+                tasks.shutdown(to_console.clone()).await;
+                error!("Error starting metrics endpoint: {e}");
+                exit(1);
+            }
+        }
+    }
+
+    let to_web = web::channel();
+    if let Some(web_port) = settings.web_port {
+        match web::init(
+            ([0, 0, 0, 0], web_port).into(),
+            to_console.clone(),
+            to_web.clone(),
+            to_main,
+            tasks.shutdown_signal(),
+        )
+        .await
+        {
+            Ok(handle) => tasks.register("admin dashboard", handle),
+            Err(e) => {
+                let _ = to_console.send(console::ToConsoleType::Quit);
+                tasks.shutdown(to_console.clone()).await;
+                error!("Error starting admin dashboard: {e}");
+                exit(1);
+            }
+        }
+    }
 
     c_debug!(to_console, "Starting up with {:?}", settings);
 
+    // `Delay` instead of the default `Burst`: if a tick's handler runs long and the loop falls
+    // behind, resume on a fresh interval from whenever we catch up rather than firing every missed
+    // tick back-to-back - a slow tick shouldn't be able to spiral into an ever-growing backlog of
+    // simulation steps all trying to run in the same `select!` pass.
     let mut world_tick = time::interval(Duration::from_millis(1000 / constants::TICKS_PER_SECOND));
+    world_tick.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
     let mut physics_tick = time::interval(Duration::from_millis(
         1000 / constants::PHYS_TICKS_PER_SECOND,
     ));
+    physics_tick.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
     let mut packet_update_tick = time::interval(Duration::from_millis(
         1000 / constants::PACKET_UPDATES_PER_SECOND,
     ));
+    packet_update_tick.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
     let mut heartbeat_tick =
         time::interval(Duration::from_secs(constants::SECONDS_BETWEEN_HEARTBEATS));
+    // Left on the default `Burst` missed-tick behavior (unlike the ticks above) so the first
+    // `.tick()` still completes immediately: the master server's listing id/play URL gets logged
+    // as soon as the server comes up, not after the first `masterlist_interval` wait.
+    let mut masterlist_tick = time::interval(Duration::from_secs(settings.masterlist_interval));
+    let mut autosave_tick = time::interval(Duration::from_secs(settings.autosave_interval));
     let mut uptime_clock = time::interval(Duration::from_secs(1));
 
+    let masterlist_client = reqwest::Client::new();
+    let masterlist_salt = rand::rng().next_u64();
+
     let spawn_point = settings
         .spawn_point
         .unwrap_or(u32::from(settings.world_width) / 2);
 
-    let world_res = World::generate(
-        to_console.clone(),
-        settings.world_width.into(),
-        settings.world_height.into(),
-        settings.chunk_size.into(),
-        spawn_point,
-        settings.spawn_range,
-        settings.world_type,
-    );
-    let mut world = match world_res {
-        Ok(w) => w,
-        Err(e) => {
-            let _ = to_console.send(console::ToConsoleType::Quit);
-            console_thread.await.unwrap();
-            error!("Error creating world: {e}");
-            exit(1);
-        }
-    };
-
     // uptime, stats
     let mut uptime = Duration::default();
     let mut last_tick_time = Duration::default();
@@ -159,33 +305,215 @@ async fn main() -> io::Result<()> {
     let mut tick_times_current: [Duration; 8] = [Duration::default(); 8];
     let mut tick_times_count: [u32; 8] = [0u32; 8];
 
+    // Bind the socket and start the network thread before the world is ready, so the server can
+    // already accept (and buffer) connections while procedurally generated terrain builds below.
     let socket_result = UdpSocket::bind(format!("0.0.0.0:{}", settings.port)).await;
     let socket = match socket_result {
         Ok(s) => s,
         Err(e) => {
             let _ = to_console.send(console::ToConsoleType::Quit);
-            console_thread.await.unwrap();
+            tasks.shutdown(to_console.clone()).await;
             error!("error binding port: {e}");
             exit(1);
         }
     };
-    let (network_thread, mut from_network, to_network) =
-        network::init(socket, to_console.clone(), settings.max_network_errors);
+    let ws_clients: network::WsClients = Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+    let (network_thread, mut from_network, to_network, network_to_main) = network::init(
+        Arc::new(socket),
+        to_console.clone(),
+        settings.max_network_errors,
+        metrics.clone(),
+        ws_clients.clone(),
+    );
+    tasks.register("network", network_thread);
+
+    if let Some(ws_port) = settings.ws_port {
+        match game_ws::init(
+            ([0, 0, 0, 0], ws_port).into(),
+            to_console.clone(),
+            network_to_main,
+            ws_clients,
+            tasks.shutdown_signal(),
+        )
+        .await
+        {
+            Ok(handle) => tasks.register("game websocket", handle),
+            Err(e) => {
+                let _ = to_console.send(console::ToConsoleType::Quit);
+                tasks.shutdown(to_console.clone()).await;
+                error!("Error starting game websocket listener: {e}");
+                exit(1);
+            }
+        }
+    }
+
+    let public_addr = if settings.nat_traversal {
+        let (nat_thread, public_addr) =
+            nat::init(to_console.clone(), settings.port, settings.stun_server.clone());
+        tasks.register("nat traversal", nat_thread);
+        public_addr
+    } else {
+        watch::channel(None).1
+    };
+
+    let plugins = match plugins::Plugins::load(&settings.plugins_dir, to_console.clone()) {
+        Ok(plugins) => plugins,
+        Err(e) => {
+            let _ = to_console.send(console::ToConsoleType::Quit);
+            tasks.shutdown(to_console.clone()).await;
+            error!("Error reading plugins directory {}: {e}", settings.plugins_dir.display());
+            exit(1);
+        }
+    };
+
+    let mut commands = chat_commands::Commands::new();
+    if let Err(e) = commands.load_operators(&settings.operators_file) {
+        let _ = to_console.send(console::ToConsoleType::Quit);
+        tasks.shutdown(to_console.clone()).await;
+        error!("Error reading operators file {}: {e}", settings.operators_file.display());
+        exit(1);
+    }
+    if let Err(e) = commands.load_bans(&settings.bans_file) {
+        let _ = to_console.send(console::ToConsoleType::Quit);
+        tasks.shutdown(to_console.clone()).await;
+        error!("Error reading bans file {}: {e}", settings.bans_file.display());
+        exit(1);
+    }
+
+    let mut world = match &settings.world_file {
+        Some(path) if path.exists() => match worldfile::load(path) {
+            Ok(loaded) => {
+                let spawn_range =
+                    NonZeroU32::new(loaded.spawn_range).unwrap_or(settings.spawn_range);
+                match World::from_save(
+                    loaded.width,
+                    loaded.height,
+                    loaded.chunk_size,
+                    loaded.spawn_point,
+                    spawn_range,
+                    loaded.seed,
+                    loaded.chunks,
+                ) {
+                    Ok(world) => {
+                        c_info!(to_console, "Loaded world from {}", path.display());
+                        world
+                    }
+                    Err(e) => {
+                        let _ = to_console.send(console::ToConsoleType::Quit);
+                        tasks.shutdown(to_console.clone()).await;
+                        error!("Error loading world from {}: {e}", path.display());
+                        exit(1);
+                    }
+                }
+            }
+            Err(e) => {
+                let _ = to_console.send(console::ToConsoleType::Quit);
+                tasks.shutdown(to_console.clone()).await;
+                error!("Error reading world file {}: {e}", path.display());
+                exit(1);
+            }
+        },
+        _ => {
+            // World generation is CPU-bound and can take a while on a large map; run it on a
+            // blocking-pool thread instead of on the async runtime so the network thread above
+            // keeps accepting and buffering connections while it builds.
+            let to_console_gen = to_console.clone();
+            let width = settings.world_width.into();
+            let height = settings.world_height.into();
+            let chunk_size = settings.chunk_size.into();
+            let spawn_range = settings.spawn_range;
+            let world_type = settings.world_type;
+            c_info!(
+                to_console,
+                "Generating world in the background; the server will start accepting players once it's ready."
+            );
+            let generate_task = tokio::task::spawn_blocking(move || {
+                World::generate(
+                    to_console_gen,
+                    width,
+                    height,
+                    chunk_size,
+                    spawn_point,
+                    spawn_range,
+                    world_type,
+                )
+            });
+            match generate_task.await {
+                Ok(Ok(w)) => w,
+                Ok(Err(e)) => {
+                    let _ = to_console.send(console::ToConsoleType::Quit);
+                    tasks.shutdown(to_console.clone()).await;
+                    error!("Error creating world: {e}");
+                    exit(1);
+                }
+                Err(e) => {
+                    let _ = to_console.send(console::ToConsoleType::Quit);
+                    tasks.shutdown(to_console.clone()).await;
+                    error!("World generation task panicked: {e}");
+                    exit(1);
+                }
+            }
+        }
+    };
+
+    match config::load(settings.config.as_deref()) {
+        Ok(server_config) => config::apply(&server_config, &mut world, &to_console),
+        Err(e) => {
+            c_warn!(
+                to_console,
+                "Error reading config file: {e}, starting with no config overrides."
+            );
+        }
+    }
+
     loop {
         tokio::select! {
             _ = tokio::signal::ctrl_c() => {
                 break;
             }
+            _ = wait_for_terminate() => {
+                break;
+            }
             packet_maybe = from_network.recv() => {
                 // hopefully will fix windows bullshit
                 match packet_maybe {
-                    Some((addr, packet)) => network::process_client_packet(to_console.clone(), to_network.clone(), packet, addr, &mut world).await?,
+                    Some((addr, network::IncomingEvent::Packet(packet))) => network::process_client_packet(to_console.clone(), to_web.clone(), to_network.clone(), packet, addr, &mut world, &plugins, &mut commands, &metrics).await?,
+                    Some((addr, network::IncomingEvent::PeerTimedOut)) => {
+                        if let Some(id) = world.players.iter().find(|p| p.addr == addr).map(|p| p.id) {
+                            world.kick(to_console.clone(), to_web.clone(), to_network.clone(), id, Some("Connection timed out.")).await?;
+                        }
+                    }
                     None => break,
                 }
             }
             _ = heartbeat_tick.tick() => {
                 if !settings.no_heartbeat {
-                    network::heartbeat(to_console.clone(), to_network.clone(), &mut world).await?;
+                    network::heartbeat(to_console.clone(), to_web.clone(), to_network.clone(), &mut world, &metrics).await?;
+                }
+            }
+            _ = autosave_tick.tick() => {
+                if let Some(path) = &settings.world_file {
+                    match worldfile::save(&world, path) {
+                        Ok(_) => c_info!(to_console, "Autosaved world to {}", path.display()),
+                        Err(e) => c_error!(to_console, "Autosave failed: {e}"),
+                    }
+                }
+            }
+            _ = masterlist_tick.tick() => {
+                if let Some(url) = &settings.heartbeat_url {
+                    let payload = masterlist::HeartbeatPayload {
+                        port: settings.port,
+                        players: world.players.len(),
+                        max_players: settings.max_players,
+                        name: &settings.server_name,
+                        public: settings.public,
+                        software_version: env!("CARGO_PKG_VERSION"),
+                        salt: masterlist_salt,
+                        public_addr: *public_addr.borrow(),
+                        world_width: settings.world_width.get(),
+                        world_height: settings.world_height.get(),
+                    };
+                    masterlist::send_heartbeat(to_console.clone(), &masterlist_client, url, &payload).await?;
                 }
             }
             _ = physics_tick.tick() => {
@@ -196,11 +524,15 @@ async fn main() -> io::Result<()> {
                 world.flush_block_queue(to_network.clone()).await?;
             }
             _ = world_tick.tick() => {
-                last_tick_time = world.world_tick(to_console.clone(), to_network.clone()).await?;
+                last_tick_time = world.world_tick(to_console.clone(), to_web.clone(), to_network.clone()).await?;
                 tick_times_current.par_iter_mut().enumerate().for_each(|(idx, time)| {
                     *time = ((*time * tick_times_count[idx]) + last_tick_time) / (tick_times_count[idx] + 1);
                 });
                 tick_times_count.par_iter_mut().for_each(|count| *count += 1);
+                let plugin_actions = plugins.on_tick(&to_console);
+                if let Err(e) = plugins::apply_actions(to_console.clone(), to_web.clone(), to_network.clone(), &mut world, plugin_actions).await {
+                    c_error!(to_console, "error applying plugin actions: {e}");
+                }
             }
             _ = uptime_clock.tick() => {
                 uptime += Duration::from_secs(1);
@@ -233,18 +565,22 @@ async fn main() -> io::Result<()> {
                 if secs % 600 == 0 {
                     save_and_reset!(tick_times_saved, tick_times_current, 7);
                 }
+                let stats = Stats {
+                    uptime,
+                    tps: 1000u128 / max(tick_times_saved[0].as_millis(), 1000u128 / constants::TICKS_PER_SECOND as u128),
+                    mspt: tick_times_saved[0],
+                    players: world.players.len()
+                };
+                metrics.connected_players.set(stats.players as i64);
+                metrics.loaded_chunks.set(world.loaded_chunk_count() as i64);
                 if !settings.no_console {
-                    let _ = to_console.send(console::ToConsoleType::Stats(Stats {
-                        uptime,
-                        tps: 1000u128 / max(tick_times_saved[0].as_millis(), 1000u128 / constants::TICKS_PER_SECOND as u128),
-                        mspt: tick_times_saved[0],
-                        players: world.players.len()
-                    }));
+                    let _ = to_console.send(console::ToConsoleType::Stats(stats.clone()));
                 }
+                let _ = to_web.send(web::WebEvent::Stats(stats));
             }
             command_opt = from_console.recv() => {
                 if let Some(command) = command_opt {
-                    if console::process_command(to_console.clone(), to_network.clone(), &mut world, command, tick_times_saved, last_tick_time, phys_last_tick_time).await? {
+                    if console::process_command(to_console.clone(), to_web.clone(), to_network.clone(), &public_addr, &mut world, command, tick_times_saved, last_tick_time, phys_last_tick_time).await? {
                         break;
                     }
                 }
@@ -255,10 +591,15 @@ async fn main() -> io::Result<()> {
     world
         .shutdown(to_console.clone(), to_network.clone())
         .await?;
+    if let Some(path) = &settings.world_file {
+        match worldfile::save(&world, path) {
+            Ok(_) => c_info!(to_console, "World saved to {}", path.display()),
+            Err(e) => c_error!(to_console, "Failed to save world: {e}"),
+        }
+    }
     let _ = to_console.send(console::ToConsoleType::Quit);
     let _ = to_network.send(network::NetworkThreadMessage::Shutdown);
-    console_thread.await.unwrap();
-    network_thread.await.unwrap();
+    tasks.shutdown(to_console.clone()).await;
 
     info!("Server shutdown complete after being up for {uptime:?}.");
     Ok(())
@@ -0,0 +1,75 @@
+use crate::console::ToConsole;
+use crate::{c_error, c_info};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::SocketAddr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MasterlistError {
+    #[error("error sending heartbeat: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// The body POSTed to the master server on every heartbeat: enough for the directory to list
+/// this server and tell players how full it is, plus a per-launch salt so the master server can
+/// tell two restarts of the same server apart from a spoofed duplicate.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeartbeatPayload<'a> {
+    pub port: u16,
+    pub players: usize,
+    pub max_players: u32,
+    pub name: &'a str,
+    pub public: bool,
+    pub software_version: &'a str,
+    pub salt: u64,
+    /// The server's public endpoint as discovered by `--nat-traversal`, if enabled and
+    /// successful. Lets the master server list a directly-dialable address instead of making
+    /// players guess it.
+    pub public_addr: Option<SocketAddr>,
+    pub world_width: u32,
+    pub world_height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeartbeatResponse {
+    id: String,
+    /// A play URL the master server hands back for this listing, if it has one to give - not
+    /// every tracker fronts a web client, so this is optional rather than required.
+    play_url: Option<String>,
+}
+
+async fn post_heartbeat(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &HeartbeatPayload<'_>,
+) -> Result<HeartbeatResponse, MasterlistError> {
+    let response = client.post(url).json(payload).send().await?;
+    let response = response.error_for_status()?;
+    Ok(response.json().await?)
+}
+
+/// Sends one heartbeat to the master server at `url`, logging the listing id (and play URL, if
+/// the tracker gave one back) or the error, if it couldn't be reached. Never fails the caller - a
+/// master server being unreachable shouldn't take the game server down with it.
+pub async fn send_heartbeat(
+    to_console: ToConsole,
+    client: &reqwest::Client,
+    url: &str,
+    payload: &HeartbeatPayload<'_>,
+) -> io::Result<()> {
+    match post_heartbeat(client, url, payload).await {
+        Ok(HeartbeatResponse {
+            id,
+            play_url: Some(play_url),
+        }) => c_info!(
+            to_console,
+            "Sent master server heartbeat, listing id: {id}, play at {play_url}"
+        ),
+        Ok(HeartbeatResponse { id, play_url: None }) => {
+            c_info!(to_console, "Sent master server heartbeat, listing id: {id}")
+        }
+        Err(e) => c_error!(to_console, "Failed to send master server heartbeat: {e}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::console::ToConsole;
+use crate::{c_error, c_info};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("error registering metric: {0}")]
+    Registration(#[from] prometheus::Error),
+    #[error("error binding metrics endpoint: {0}")]
+    Bind(#[from] hyper::Error),
+}
+
+/// Every counter/gauge the `/metrics` endpoint exposes. Every field is itself an `Arc`-backed
+/// `prometheus` handle, so `Metrics` is cheap to clone and gets threaded into `network::init()`,
+/// `process_client_packet` and `heartbeat()` the same way `ToConsole`/`ToNetwork` are.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    /// Packets received, labeled by `packet_type` (see [`crate::network::PacketTypes::type_name`]).
+    pub packets_received: IntCounterVec,
+    /// Packets sent. Not labeled by type - by the time the network thread frames and sends a
+    /// packet it's already opaque bytes, so splitting this out would mean threading a label all
+    /// the way from every `encode_and_send!`/`encode_and_send_reliable!` call site instead of
+    /// just the network thread's one send point.
+    pub packets_sent: IntCounter,
+    pub bytes_received: IntCounter,
+    pub bytes_sent: IntCounter,
+    pub decode_failures: IntCounter,
+    pub players_kicked_inactive: IntCounter,
+    pub blocks_placed: IntCounter,
+    pub blocks_broken: IntCounter,
+    pub connected_players: IntGauge,
+    pub loaded_chunks: IntGauge,
+    pub network_error_strikes: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self, MetricsError> {
+        let registry = Registry::new();
+
+        let packets_received = IntCounterVec::new(
+            Opts::new(
+                "yourcraft_packets_received_total",
+                "Packets received over UDP, by PacketTypes variant",
+            ),
+            &["packet_type"],
+        )?;
+        let packets_sent = IntCounter::new("yourcraft_packets_sent_total", "Packets sent over UDP")?;
+        let bytes_received = IntCounter::new("yourcraft_bytes_received_total", "Bytes received over UDP")?;
+        let bytes_sent = IntCounter::new("yourcraft_bytes_sent_total", "Bytes sent over UDP")?;
+        let decode_failures = IntCounter::new(
+            "yourcraft_decode_failures_total",
+            "Received datagrams that failed to decode as a PacketTypes",
+        )?;
+        let players_kicked_inactive = IntCounter::new(
+            "yourcraft_players_kicked_inactive_total",
+            "Players kicked for missing heartbeats",
+        )?;
+        let blocks_placed = IntCounter::new("yourcraft_blocks_placed_total", "Blocks placed by clients")?;
+        let blocks_broken = IntCounter::new("yourcraft_blocks_broken_total", "Blocks broken by clients")?;
+        let connected_players = IntGauge::new("yourcraft_connected_players", "Currently connected players")?;
+        let loaded_chunks = IntGauge::new(
+            "yourcraft_loaded_chunks",
+            "Chunks currently loaded by at least one player",
+        )?;
+        let network_error_strikes = IntGauge::new(
+            "yourcraft_network_error_strikes",
+            "Network error strikes accumulated since the network thread started",
+        )?;
+
+        registry.register(Box::new(packets_received.clone()))?;
+        registry.register(Box::new(packets_sent.clone()))?;
+        registry.register(Box::new(bytes_received.clone()))?;
+        registry.register(Box::new(bytes_sent.clone()))?;
+        registry.register(Box::new(decode_failures.clone()))?;
+        registry.register(Box::new(players_kicked_inactive.clone()))?;
+        registry.register(Box::new(blocks_placed.clone()))?;
+        registry.register(Box::new(blocks_broken.clone()))?;
+        registry.register(Box::new(connected_players.clone()))?;
+        registry.register(Box::new(loaded_chunks.clone()))?;
+        registry.register(Box::new(network_error_strikes.clone()))?;
+
+        Ok(Self {
+            registry,
+            packets_received,
+            packets_sent,
+            bytes_received,
+            bytes_sent,
+            decode_failures,
+            players_kicked_inactive,
+            blocks_placed,
+            blocks_broken,
+            connected_players,
+            loaded_chunks,
+            network_error_strikes,
+        })
+    }
+
+    /// Renders every registered metric in Prometheus's text exposition format.
+    fn render(&self) -> Vec<u8> {
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .expect("encoding gathered metrics cannot fail");
+        buffer
+    }
+}
+
+/// Starts the `/metrics` HTTP endpoint on `bind_addr`, serving `metrics`'s current snapshot on
+/// every request regardless of path or method. The returned handle is meant to be registered with
+/// a [`crate::tasks::TaskRunner`] so it gets a chance to finish its in-flight response during
+/// shutdown, same as the admin web dashboard's listener.
+pub async fn init(
+    bind_addr: SocketAddr,
+    to_console: ToConsole,
+    metrics: Metrics,
+) -> Result<JoinHandle<()>, MetricsError> {
+    let make_svc = make_service_fn(move |_conn| {
+        let metrics = metrics.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |_req: Request<Body>| {
+                let metrics = metrics.clone();
+                async move { Ok::<_, Infallible>(Response::new(Body::from(metrics.render()))) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&bind_addr)?.serve(make_svc);
+    c_info!(to_console, "Metrics endpoint listening on {bind_addr}");
+    Ok(tokio::spawn(async move {
+        if let Err(e) = server.await {
+            c_error!(to_console, "metrics endpoint stopped: {e}");
+        }
+    }))
+}
@@ -0,0 +1,205 @@
+use crate::console::ToConsole;
+use crate::{c_error, c_info, c_warn};
+use rand::RngCore;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::net::UdpSocket;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// How often an established UPnP-IGD lease is refreshed, and the public address re-queried.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How long a UPnP-IGD port mapping is leased for before it needs refreshing.
+const LEASE_SECONDS: u32 = 15 * 60;
+/// How long to wait for a STUN reflector to answer before giving up on this round.
+const STUN_TIMEOUT: Duration = Duration::from_secs(3);
+
+const STUN_MAGIC_COOKIE: u32 = 0x2112_A442;
+const STUN_BINDING_REQUEST: u16 = 0x0001;
+const STUN_ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const STUN_ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+#[derive(Error, Debug)]
+pub enum NatError {
+    #[error("network error talking to STUN reflector: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("no response from STUN reflector {0}")]
+    StunTimeout(String),
+    #[error("malformed STUN response")]
+    StunMalformed,
+    #[error("no UPnP-IGD gateway found on the local network: {0}")]
+    IgdSearch(#[from] igd_next::SearchError),
+    #[error("UPnP-IGD gateway rejected the port mapping request: {0}")]
+    IgdMap(#[from] igd_next::AddPortError),
+}
+
+/// The server's best-known public endpoint, updated every [`REFRESH_INTERVAL`] while
+/// `--nat-traversal` is enabled. `None` until the first STUN query succeeds (or forever, if it
+/// never does - NAT traversal is advisory, never fatal).
+pub type PublicAddr = watch::Receiver<Option<SocketAddr>>;
+
+/// Sends a single STUN binding request to `stun_server` from `socket` and returns the
+/// `SocketAddr` the reflector says the request came from - i.e. this socket's mapping on the
+/// public side of any NAT between it and the reflector.
+///
+/// The IP this returns is reliable for the overwhelming majority of home routers (which use
+/// endpoint-independent mapping), but the port is only a best-effort hint: `socket` isn't
+/// necessarily bound to the same local port as the game socket, and even when it is, some NATs
+/// hand out a different external port per destination. That's why `--nat-traversal` also asks
+/// the gateway for an explicit UPnP-IGD forward of `--port` rather than relying on STUN alone.
+async fn stun_query(socket: &UdpSocket, stun_server: &str) -> Result<SocketAddr, NatError> {
+    let mut transaction_id = [0u8; 12];
+    rand::rng().fill_bytes(&mut transaction_id);
+
+    let mut request = Vec::with_capacity(20);
+    request.extend_from_slice(&STUN_BINDING_REQUEST.to_be_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes());
+    request.extend_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(&transaction_id);
+
+    socket.send_to(&request, stun_server).await?;
+
+    let mut buf = [0u8; 512];
+    let len = time::timeout(STUN_TIMEOUT, socket.recv_from(&mut buf))
+        .await
+        .map_err(|_| NatError::StunTimeout(stun_server.to_string()))??
+        .0;
+
+    parse_stun_response(&buf[..len], &transaction_id)
+}
+
+fn parse_stun_response(buf: &[u8], transaction_id: &[u8; 12]) -> Result<SocketAddr, NatError> {
+    if buf.len() < 20 || buf[4..20] != *transaction_id_with_cookie(transaction_id) {
+        return Err(NatError::StunMalformed);
+    }
+    let mut offset = 20;
+    while offset + 4 <= buf.len() {
+        let attr_type = u16::from_be_bytes([buf[offset], buf[offset + 1]]);
+        let attr_len = u16::from_be_bytes([buf[offset + 2], buf[offset + 3]]) as usize;
+        let value_start = offset + 4;
+        let value_end = value_start + attr_len;
+        if value_end > buf.len() {
+            break;
+        }
+        let value = &buf[value_start..value_end];
+        match attr_type {
+            STUN_ATTR_XOR_MAPPED_ADDRESS => {
+                return decode_mapped_address(value, Some(transaction_id))
+            }
+            STUN_ATTR_MAPPED_ADDRESS => return decode_mapped_address(value, None),
+            _ => {}
+        }
+        // Attributes are padded to a 4-byte boundary.
+        offset = value_end + (4 - attr_len % 4) % 4;
+    }
+    Err(NatError::StunMalformed)
+}
+
+fn transaction_id_with_cookie(transaction_id: &[u8; 12]) -> Box<[u8; 16]> {
+    let mut buf = [0u8; 16];
+    buf[..4].copy_from_slice(&STUN_MAGIC_COOKIE.to_be_bytes());
+    buf[4..].copy_from_slice(transaction_id);
+    Box::new(buf)
+}
+
+/// Decodes a (XOR-)MAPPED-ADDRESS attribute body. `xor_transaction_id` is `Some` for
+/// XOR-MAPPED-ADDRESS (unmasked against the magic cookie and this response's transaction id) and
+/// `None` for a plain MAPPED-ADDRESS.
+fn decode_mapped_address(
+    value: &[u8],
+    xor_transaction_id: Option<&[u8; 12]>,
+) -> Result<SocketAddr, NatError> {
+    if value.len() < 8 {
+        return Err(NatError::StunMalformed);
+    }
+    let family = value[1];
+    let mut port = u16::from_be_bytes([value[2], value[3]]);
+    if xor_transaction_id.is_some() {
+        port ^= (STUN_MAGIC_COOKIE >> 16) as u16;
+    }
+    let ip = match family {
+        0x01 if value.len() >= 8 => {
+            let mut octets = [value[4], value[5], value[6], value[7]];
+            if xor_transaction_id.is_some() {
+                for (octet, cookie_byte) in octets.iter_mut().zip(STUN_MAGIC_COOKIE.to_be_bytes())
+                {
+                    *octet ^= cookie_byte;
+                }
+            }
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        0x02 if value.len() >= 20 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&value[4..20]);
+            if let Some(transaction_id) = xor_transaction_id {
+                let mask = transaction_id_with_cookie(transaction_id);
+                for (octet, mask_byte) in octets.iter_mut().zip(mask.iter()) {
+                    *octet ^= mask_byte;
+                }
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return Err(NatError::StunMalformed),
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Finds this machine's LAN-facing IPv4 address by asking the OS which local interface it would
+/// route a packet to a public address through - no packet is actually sent.
+async fn local_ipv4() -> Result<Ipv4Addr, NatError> {
+    let probe = UdpSocket::bind("0.0.0.0:0").await?;
+    probe.connect("8.8.8.8:80").await?;
+    match probe.local_addr()? {
+        SocketAddr::V4(addr) => Ok(*addr.ip()),
+        SocketAddr::V6(_) => Ok(Ipv4Addr::UNSPECIFIED),
+    }
+}
+
+/// Asks a UPnP-IGD gateway on the local network to forward external UDP `port` to this machine
+/// for [`LEASE_SECONDS`]. A no-op, loudly logged warning if no gateway answers - routers without
+/// UPnP, or with it disabled, are common and shouldn't stop the server from starting.
+async fn map_port(to_console: ToConsole, port: u16) -> Result<(), NatError> {
+    let gateway = igd_next::aio::tokio::search_gateway(igd_next::SearchOptions::default()).await?;
+    let local_addr = local_ipv4().await?;
+    gateway
+        .add_port(
+            igd_next::PortMappingProtocol::UDP,
+            port,
+            std::net::SocketAddrV4::new(local_addr, port),
+            LEASE_SECONDS,
+            "yourcraft game server",
+        )
+        .await?;
+    c_info!(to_console, "Mapped UDP port {port} via UPnP-IGD (lease {LEASE_SECONDS}s)");
+    Ok(())
+}
+
+/// Starts the NAT traversal background task: on a [`REFRESH_INTERVAL`] timer it queries
+/// `stun_server` for this server's public address and (re-)requests a UPnP-IGD forward of
+/// `port`, publishing whatever address it learns on the returned [`PublicAddr`]. Both steps are
+/// best-effort - failures are logged and retried next tick, never returned to the caller.
+pub fn init(to_console: ToConsole, port: u16, stun_server: String) -> (JoinHandle<()>, PublicAddr) {
+    let (tx, rx) = watch::channel(None);
+    let handle = tokio::spawn(async move {
+        let mut interval = time::interval(REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            match UdpSocket::bind("0.0.0.0:0").await {
+                Ok(socket) => match stun_query(&socket, &stun_server).await {
+                    Ok(addr) => {
+                        c_info!(to_console, "Discovered public address {addr} via STUN ({stun_server})");
+                        let _ = tx.send(Some(addr));
+                    }
+                    Err(e) => c_warn!(to_console, "STUN discovery via {stun_server} failed: {e}"),
+                },
+                Err(e) => c_error!(to_console, "Could not open a socket for STUN discovery: {e}"),
+            }
+            if let Err(e) = map_port(to_console.clone(), port).await {
+                c_warn!(to_console, "UPnP-IGD port mapping failed: {e}");
+            }
+        }
+    });
+    (handle, rx)
+}
@@ -1,20 +1,109 @@
+use crate::chat_commands::Commands;
 use crate::console::ToConsole;
+use crate::metrics::Metrics;
 use crate::player::{Item, ItemStack, Player};
+use crate::plugins::{self, Plugins};
+use crate::updates::{self, Update};
+use crate::web::{ToWeb, WebEvent};
 use crate::world::{is_solid, Block, Chunk, World, WorldError};
 use crate::{c_debug, c_error, c_info, c_warn, constants};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 use rand::prelude::*;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_pickle::{from_slice, to_vec, DeOptions, SerOptions};
-use std::io;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::UdpSocket;
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use tokio::task::JoinHandle;
+use tokio::time;
+
+/// Serialized packets at or above this size are deflated before being handed to the network
+/// thread - same idea as [`NetworkChunk`]'s own RLE/deflate threshold, just applied to the outer
+/// envelope so large non-chunk packets (a big `ServerUpdateInventory`, a long chat backlog, ...)
+/// benefit too. Below it, deflate's header/footer overhead costs more than it saves.
+const PACKET_COMPRESSION_THRESHOLD: usize = 256;
+/// Whether a [`PacketTypes::to_bytes`] envelope's payload is the raw pickled packet.
+const PACKET_COMPRESSION_RAW: u8 = 0;
+/// Whether a [`PacketTypes::to_bytes`] envelope's payload was zlib-deflated.
+const PACKET_COMPRESSION_DEFLATE: u8 = 1;
 
 impl PacketTypes {
+    /// Pickles `self`, deflating the result first if it's at or above
+    /// [`PACKET_COMPRESSION_THRESHOLD`] - in which case a leading compression flag byte tells
+    /// [`PacketTypes::from_bytes`] to inflate before unpickling. Both the UDP listener and the
+    /// WebSocket transport send exactly what this produces (see [`game_ws`](crate::game_ws)), so
+    /// the flag byte has to stay in sync with [`PacketTypes::from_bytes`] on both.
     pub fn to_bytes(&self) -> serde_pickle::Result<Vec<u8>> {
-        to_vec(self, SerOptions::new())
+        let data = to_vec(self, SerOptions::new())?;
+        let (flag, data) = if data.len() >= PACKET_COMPRESSION_THRESHOLD {
+            match deflate(&data) {
+                Ok(deflated) if deflated.len() < data.len() => {
+                    (PACKET_COMPRESSION_DEFLATE, deflated)
+                }
+                _ => (PACKET_COMPRESSION_RAW, data),
+            }
+        } else {
+            (PACKET_COMPRESSION_RAW, data)
+        };
+        let mut envelope = Vec::with_capacity(1 + data.len());
+        envelope.push(flag);
+        envelope.extend_from_slice(&data);
+        Ok(envelope)
+    }
+
+    /// Reverses [`PacketTypes::to_bytes`]'s envelope - inflating `bytes[1..]` first if `bytes[0]`
+    /// is [`PACKET_COMPRESSION_DEFLATE`] - before unpickling.
+    pub fn from_bytes(bytes: &[u8]) -> serde_pickle::Result<PacketTypes> {
+        match bytes.split_first() {
+            Some((&PACKET_COMPRESSION_DEFLATE, data)) => match inflate(data) {
+                Ok(inflated) => from_slice(&inflated, DeOptions::new()),
+                Err(_) => from_slice(data, DeOptions::new()),
+            },
+            Some((_, data)) => from_slice(data, DeOptions::new()),
+            None => from_slice(bytes, DeOptions::new()),
+        }
+    }
+
+    /// The variant's name, used as the `packet_type` label on the
+    /// [`crate::metrics::Metrics::packets_received`] counter.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            PacketTypes::ClientHello { .. } => "ClientHello",
+            PacketTypes::ServerSync { .. } => "ServerSync",
+            PacketTypes::ClientRequestChunk { .. } => "ClientRequestChunk",
+            PacketTypes::ServerChunkResponse { .. } => "ServerChunkResponse",
+            PacketTypes::ClientUnloadChunk { .. } => "ClientUnloadChunk",
+            PacketTypes::ServerPlayerJoin { .. } => "ServerPlayerJoin",
+            PacketTypes::ServerPlayerEnterLoaded { .. } => "ServerPlayerEnterLoaded",
+            PacketTypes::ServerPlayerLeaveLoaded { .. } => "ServerPlayerLeaveLoaded",
+            PacketTypes::ServerPlayerLeave { .. } => "ServerPlayerLeave",
+            PacketTypes::ClientGoodbye {} => "ClientGoodbye",
+            PacketTypes::ClientPlaceBlock { .. } => "ClientPlaceBlock",
+            PacketTypes::ServerUpdateBlock { .. } => "ServerUpdateBlock",
+            PacketTypes::ServerBatchUpdateBlock { .. } => "ServerBatchUpdateBlock",
+            PacketTypes::ClientPlayerXVelocity { .. } => "ClientPlayerXVelocity",
+            PacketTypes::ClientPlayerJump {} => "ClientPlayerJump",
+            PacketTypes::ClientPlayerRespawn {} => "ClientPlayerRespawn",
+            PacketTypes::ServerPlayerUpdatePos { .. } => "ServerPlayerUpdatePos",
+            PacketTypes::ServerKick { .. } => "ServerKick",
+            PacketTypes::ServerHeartbeat {} => "ServerHeartbeat",
+            PacketTypes::ClientHeartbeat {} => "ClientHeartbeat",
+            PacketTypes::ClientSendMessage { .. } => "ClientSendMessage",
+            PacketTypes::ServerSendMessage { .. } => "ServerSendMessage",
+            PacketTypes::ClientBreakBlock { .. } => "ClientBreakBlock",
+            PacketTypes::ClientTryAttack { .. } => "ClientTryAttack",
+            PacketTypes::ClientChangeSlot { .. } => "ClientChangeSlot",
+            PacketTypes::ServerUpdateHealth { .. } => "ServerUpdateHealth",
+            PacketTypes::ServerUpdateInventory { .. } => "ServerUpdateInventory",
+            PacketTypes::ClientRequestCraft { .. } => "ClientRequestCraft",
+            PacketTypes::ClientAck { .. } => "ClientAck",
+            PacketTypes::ServerAck { .. } => "ServerAck",
+        }
     }
 }
 
@@ -25,23 +114,126 @@ pub struct ClientConnection {
     pub id: u32,
     pub server_player: Player,
     pub connection_alive: bool,
+    pub channel: ChatChannel,
+    /// The protocol version this connection's `ClientHello` negotiated - always one of
+    /// [`SUPPORTED_PROTOCOLS`], since anything else is kicked before a `ClientConnection` exists.
+    pub protocol_version: u32,
+}
+
+/// Which chat channel a non-whisper [`PacketTypes::ClientSendMessage`] is routed to - see that
+/// branch in `process_client_packet`. Membership lives on [`ClientConnection::channel`] and is
+/// switched with the `/channel` command; [`ChatChannel::Local`] isn't membership at all, just a
+/// standing request to compute recipients by [`crate::constants::MAX_INTERACT_RANGE`] proximity,
+/// the same distance check `ClientTryAttack` uses.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChatChannel {
+    Global,
+    Local,
+    Team(String),
 }
 
+/// `blocks` is raw, one byte per block - no encoding applied.
+const CHUNK_COMPRESSION_RAW: u8 = 0;
+/// `blocks` is run-length encoded: alternating `(block_id, run_length)` byte pairs, runs longer
+/// than 255 blocks split across consecutive pairs of the same `block_id`. See [`rle_encode`].
+const CHUNK_COMPRESSION_RLE: u8 = 1;
+/// `blocks` is RLE-encoded (as above) then zlib-deflated.
+const CHUNK_COMPRESSION_RLE_DEFLATE: u8 = 2;
+
+/// RLE output above this many bytes is also piped through zlib deflate, since RLE alone won't
+/// help much on noisy (e.g. terrain-generated) chunks where deflate's dictionary still finds
+/// repetition RLE can't express. Below it, deflate's header/footer overhead isn't worth paying.
+const CHUNK_DEFLATE_THRESHOLD: usize = 256;
+
+/// A chunk's blocks as sent over the wire, in whichever of [`CHUNK_COMPRESSION_RAW`] /
+/// [`CHUNK_COMPRESSION_RLE`] / [`CHUNK_COMPRESSION_RLE_DEFLATE`] `From<Chunk>` found smallest.
+/// Since `ServerChunkResponse` is sent reliably (see `encode_and_send_reliable!`) and the network
+/// thread's receive buffer is sized for [`RECV_BUFFER_SIZE`], a single `NetworkChunk` - framing,
+/// pickling and compression overhead included - must stay comfortably under that to avoid being
+/// truncated; worst case (incompressible, `CHUNK_COMPRESSION_RAW`) that's `size * size` bytes.
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq)]
 pub struct NetworkChunk {
     size: u32,
     chunk_x: u32,
     chunk_y: u32,
+    compression: u8,
     blocks: Vec<u8>,
+    /// `(skylight << 4) | block_light` per cell, same row-major order as `blocks`. Always raw -
+    /// light varies too unpredictably cell-to-cell for RLE/deflate to pay off the way it does on
+    /// mostly-uniform terrain.
+    light: Vec<u8>,
 }
 
-impl From<Chunk> for NetworkChunk {
-    fn from(chunk: Chunk) -> Self {
+/// Run-length encodes `blocks` as alternating `(block_id, run_length)` byte pairs. A run longer
+/// than 255 blocks is split across as many consecutive pairs of the same `block_id` as needed.
+fn rle_encode(blocks: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut iter = blocks.iter().peekable();
+    while let Some(&block) = iter.next() {
+        let mut run: u8 = 1;
+        while run < u8::MAX && iter.peek() == Some(&&block) {
+            iter.next();
+            run += 1;
+        }
+        encoded.push(block);
+        encoded.push(run);
+    }
+    encoded
+}
+
+/// Deflates `data` with zlib, for RLE output that's still large (e.g. noisy terrain chunks).
+fn deflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Reverses [`deflate`].
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decoder = ZlibDecoder::new(data);
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+impl NetworkChunk {
+    /// Builds the wire chunk for `chunk`, including the light level `world` has computed for each
+    /// of its cells. Light is tracked out-of-band on `World` (same as `water_levels`), not on
+    /// `Chunk` itself, so converting a chunk alone can't produce it - hence this takes `world`
+    /// as well, rather than a plain `From<Chunk>`.
+    pub fn with_light(chunk: Chunk, world: &World) -> Self {
+        let size = chunk.size;
+        let chunk_x = chunk.chunk_x;
+        let chunk_y = chunk.chunk_y;
+        let light: Vec<u8> = (0..size * size)
+            .map(|cell| {
+                let local_x = cell % size;
+                let local_y = cell / size;
+                world.light_at(chunk_x * size + local_x, chunk_y * size + local_y)
+            })
+            .collect();
+
+        let raw: Vec<u8> = chunk.into_blocks().into_par_iter().map(|bl| bl.into()).collect();
+        let rle = rle_encode(&raw);
+        let (compression, blocks) = if rle.len() >= raw.len() {
+            (CHUNK_COMPRESSION_RAW, raw)
+        } else if rle.len() > CHUNK_DEFLATE_THRESHOLD {
+            match deflate(&rle) {
+                Ok(deflated) if deflated.len() < rle.len() => {
+                    (CHUNK_COMPRESSION_RLE_DEFLATE, deflated)
+                }
+                _ => (CHUNK_COMPRESSION_RLE, rle),
+            }
+        } else {
+            (CHUNK_COMPRESSION_RLE, rle)
+        };
         Self {
-            size: chunk.size,
-            chunk_x: chunk.chunk_x,
-            chunk_y: chunk.chunk_y,
-            blocks: chunk.blocks.into_par_iter().map(|bl| bl.into()).collect(),
+            size,
+            chunk_x,
+            chunk_y,
+            compression,
+            blocks,
+            light,
         }
     }
 }
@@ -50,6 +242,9 @@ impl From<Chunk> for NetworkChunk {
 pub struct NetworkItemStack {
     item: u8,
     count: u8,
+    /// Accumulated durability damage, so clients can render a wear bar (`damage` /
+    /// `Item::max_durability`). Always `0` for non-damageable items.
+    damage: u16,
 }
 
 impl From<ItemStack> for NetworkItemStack {
@@ -57,6 +252,7 @@ impl From<ItemStack> for NetworkItemStack {
         Self {
             item: stack.item.into(),
             count: stack.count.get(),
+            damage: stack.damage,
         }
     }
 }
@@ -69,6 +265,8 @@ impl ClientConnection {
             addr: old.addr,
             server_player: new_player,
             connection_alive: old.connection_alive,
+            channel: old.channel.clone(),
+            protocol_version: old.protocol_version,
         }
     }
 
@@ -77,6 +275,7 @@ impl ClientConnection {
         world: &World,
         x: u32,
         name: String,
+        protocol_version: u32,
     ) -> Result<ClientConnection, WorldError> {
         Ok(ClientConnection {
             addr,
@@ -84,16 +283,28 @@ impl ClientConnection {
             server_player: Player::spawn_at(world, x)?,
             id: rand::rng().next_u32(),
             connection_alive: true,
+            protocol_version,
+            channel: ChatChannel::Global,
         })
     }
 }
 
+/// Bumped whenever a packet layout changes in a way an older client can't safely ignore. Checked
+/// against [`SUPPORTED_PROTOCOLS`] on every `ClientHello` so a stale client gets a clear
+/// [`PacketTypes::ServerKick`] instead of a confusing desync further down the line.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Every protocol version this server will still accept a `ClientHello` from. Kept separate from
+/// [`PROTOCOL_VERSION`] so a transitional server build can widen this to accept an outgoing
+/// client protocol alongside its new one, instead of kicking every client the instant it bumps.
+pub const SUPPORTED_PROTOCOLS: &[u32] = &[PROTOCOL_VERSION];
+
 // Use the macro to define packets
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[repr(u8)]
 pub enum PacketTypes {
     ClientHello {
         name: String,
+        protocol_version: u32,
     },
     ServerSync {
         player_id: u32,
@@ -163,6 +374,9 @@ pub enum PacketTypes {
     ClientHeartbeat {},
     ClientSendMessage {
         msg: String,
+        /// `Some(player_id)` whispers this to just that player instead of routing it through the
+        /// sender's [`ChatChannel`] like a normal message.
+        target: Option<u32>,
     },
     ServerSendMessage {
         player_name: String,
@@ -188,6 +402,16 @@ pub enum PacketTypes {
     ClientRequestCraft {
         item: u8,
     },
+    /// Acks a reliably-delivered packet sent by the server, letting it retire that `seq` from its
+    /// retransmit buffer. Sent by the client; never itself sent reliably.
+    ClientAck {
+        seq: u32,
+    },
+    /// Acks a reliably-delivered packet sent by the client, letting it retire that `seq` from its
+    /// retransmit buffer. Sent by the server; never itself sent reliably.
+    ServerAck {
+        seq: u32,
+    },
 }
 
 #[macro_export]
@@ -200,43 +424,263 @@ macro_rules! encode_and_send {
     };
 }
 
+/// Like [`encode_and_send!`], but asks the network thread to retry delivery (with a per-peer
+/// sequence number and exponential backoff) until the peer acks it, instead of firing and
+/// forgetting. Use this for packets whose loss would desync the client (inventory, block
+/// updates, chunk data, kicks) - never for the high-frequency position/velocity packets, which
+/// would rather drop a frame than head-of-line-block behind a retransmit.
+#[macro_export]
+macro_rules! encode_and_send_reliable {
+    ($to_network: expr, $packet: expr, $addr: expr) => {
+        let encoded = $packet.to_bytes().unwrap();
+        let _ = $to_network.send($crate::network::NetworkThreadMessage::ReliablePacket(
+            $addr, encoded,
+        ));
+    };
+}
+
+/// How often the network thread checks every peer's unacked reliable packets for ones due a
+/// retransmit.
+const RETRANSMIT_TICK: Duration = Duration::from_millis(100);
+/// The retransmit timeout a reliable packet starts with.
+const INITIAL_RTO: Duration = Duration::from_millis(200);
+/// The retransmit timeout is doubled on every retry, capped here.
+const MAX_RTO: Duration = Duration::from_secs(2);
+/// How far ahead of the next expected sequence number an out-of-order reliable packet is still
+/// accepted into the dedup window, rather than dropped as implausible.
+const RECV_WINDOW: u32 = 64;
+/// The largest `fragment_count` a reliable message is allowed to claim. Comfortably above what any
+/// real message (a `ServerChunkResponse` included) needs at [`MAX_FRAGMENT_PAYLOAD`] per fragment,
+/// but far below `u16::MAX` - without this, a peer could claim a message has tens of thousands of
+/// fragments and dribble in just enough of them to keep a [`Reassembly`] alive indefinitely.
+const MAX_FRAGMENTS_PER_MESSAGE: u16 = 256;
+/// How long an incomplete [`Reassembly`] is kept waiting for its remaining fragments before
+/// [`PeerReliability`]'s sweep in the network thread's retransmit tick evicts it. Bounds how much
+/// memory a peer that never finishes sending a message can pin, on top of the `seq`/fragment-count
+/// checks in [`incoming_packet_handler`].
+const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+const FRAME_UNRELIABLE: u8 = 0;
+const FRAME_RELIABLE: u8 = 1;
+
+/// A reliable frame's header: tag(1) + seq(4) + fragment_index(2) + fragment_count(2).
+const FRAGMENT_HEADER_LEN: usize = 9;
+/// The largest chunk of a reliable message's payload that goes in one fragment. Kept well under
+/// common path MTUs (not just the datagram-size limit) so a big `ServerChunkResponse` doesn't
+/// rely on IP-level fragmentation - which only needs one constituent fragment lost for the
+/// whole datagram to vanish - to arrive intact.
+const MAX_FRAGMENT_PAYLOAD: usize = 1200;
+
+/// The network thread's UDP receive buffer size. UDP datagrams can carry far more than the old
+/// 1024-byte buffer allowed - this is sized to comfortably fit a `ServerChunkResponse` for a
+/// 16x16 chunk (the default `--chunk-size`) even at [`CHUNK_COMPRESSION_RAW`], plus framing and
+/// pickling overhead. A bigger `--chunk-size` relies on `NetworkChunk`'s RLE/deflate compression
+/// to still fit - an incompressible chunk wider than this will be silently truncated by `recv_from`.
+const RECV_BUFFER_SIZE: usize = 8192;
+
+/// `pub(crate)` (rather than private) so a future wire-format test - an in-process client
+/// encoding/decoding these same frames against a real `network::init` socket - has something to
+/// call without duplicating the framing. Not adding that harness in this change: this crate has
+/// no automated tests of any kind yet, on either tree, so a bot-client-plus-`Arc<RwLock<World>>`
+/// integration test would be both this repo's first test and its first shared-`World` access
+/// pattern at once - more to get right without a compiler/test run to check it against than is
+/// safe to hand-verify here. Exposing the framing helpers is the real, self-contained piece of
+/// this that doesn't depend on that.
+pub(crate) fn frame_unreliable(payload: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(1 + payload.len());
+    framed.push(FRAME_UNRELIABLE);
+    framed.extend_from_slice(payload);
+    framed
+}
+
+/// Splits `payload` into one or more fragments, each framed as `[FRAME_RELIABLE][seq]
+/// [fragment_index][fragment_count][chunk]` and independently sized to fit [`MAX_FRAGMENT_PAYLOAD`].
+/// All fragments share `seq` - the receiver reassembles them keyed by it before treating the
+/// message as received at all (see [`Reassembly`]).
+/// Parses a reliable frame's header (everything [`frame_reliable`] adds besides the chunk
+/// itself), returning `(seq, fragment_index, fragment_count, chunk)`, or `None` if `framed` is
+/// too short to contain one. `pub(crate)` for the same reason as [`frame_unreliable`].
+pub(crate) fn parse_reliable_frame(framed: &[u8]) -> Option<(u32, u16, u16, &[u8])> {
+    if framed.len() < FRAGMENT_HEADER_LEN {
+        return None;
+    }
+    let seq = u32::from_be_bytes([framed[1], framed[2], framed[3], framed[4]]);
+    let fragment_index = u16::from_be_bytes([framed[5], framed[6]]);
+    let fragment_count = u16::from_be_bytes([framed[7], framed[8]]).max(1);
+    Some((seq, fragment_index, fragment_count, &framed[FRAGMENT_HEADER_LEN..]))
+}
+
+pub(crate) fn frame_reliable(seq: u32, payload: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = if payload.is_empty() {
+        vec![&[]]
+    } else {
+        payload.chunks(MAX_FRAGMENT_PAYLOAD).collect()
+    };
+    let fragment_count = chunks.len() as u16;
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(fragment_index, chunk)| {
+            let mut framed = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+            framed.push(FRAME_RELIABLE);
+            framed.extend_from_slice(&seq.to_be_bytes());
+            framed.extend_from_slice(&(fragment_index as u16).to_be_bytes());
+            framed.extend_from_slice(&fragment_count.to_be_bytes());
+            framed.extend_from_slice(chunk);
+            framed
+        })
+        .collect()
+}
+
+/// A reliable message sent but not fully acked, kept around so the retransmit timer can resend
+/// every fragment as a batch.
+struct UnackedPacket {
+    /// The fully-framed fragment datagrams (tag + seq + fragment header + chunk), ready to
+    /// resend as-is.
+    fragments: Vec<Vec<u8>>,
+    sent_at: Instant,
+    rto: Duration,
+    retries: u8,
+}
+
+/// Fragments of a not-yet-complete incoming reliable message, keyed by `fragment_index` until
+/// every one of `fragment_count` has arrived.
+struct Reassembly {
+    fragment_count: u16,
+    fragments: HashMap<u16, Vec<u8>>,
+    /// When the first fragment of this message arrived - see [`REASSEMBLY_TIMEOUT`].
+    started_at: Instant,
+}
+
+impl Reassembly {
+    fn new(fragment_count: u16) -> Self {
+        Self {
+            fragment_count,
+            fragments: HashMap::new(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Records `fragment_index`'s `chunk`. Once every fragment has arrived, returns the
+    /// reassembled payload in order; until then, returns `None`.
+    fn receive(&mut self, fragment_index: u16, chunk: Vec<u8>) -> Option<Vec<u8>> {
+        self.fragments.insert(fragment_index, chunk);
+        if self.fragments.len() < self.fragment_count as usize {
+            return None;
+        }
+        let mut payload = Vec::new();
+        for i in 0..self.fragment_count {
+            payload.extend_from_slice(self.fragments.get(&i)?);
+        }
+        Some(payload)
+    }
+}
+
+/// Per-peer reliability state: a send-side sequence counter plus unacked buffer for packets this
+/// server sends to that peer, and a receive-side dedup window plus fragment reassembly buffer
+/// for reliable packets that peer sends to us.
+#[derive(Default)]
+struct PeerReliability {
+    next_send_seq: u32,
+    unacked: HashMap<u32, UnackedPacket>,
+    /// The lowest sequence number not yet seen from this peer; every seq below it has already
+    /// been delivered (or superseded) exactly once.
+    recv_next: u32,
+    /// Out-of-order sequence numbers received ahead of `recv_next`, not yet contiguous with it.
+    recv_seen: HashSet<u32>,
+    /// Messages with at least one fragment received but not yet fully reassembled.
+    reassembly: HashMap<u32, Reassembly>,
+}
+
+impl PeerReliability {
+    /// Records that reliable `seq` was just received (fully reassembled) from this peer. Returns
+    /// `true` the first time a given `seq` is seen (the caller should act on it) and `false` for
+    /// a retransmitted duplicate (the caller should still re-ack it, just not reprocess it).
+    fn mark_received(&mut self, seq: u32) -> bool {
+        if seq < self.recv_next || seq >= self.recv_next + RECV_WINDOW {
+            // Already delivered (old duplicate) or implausibly far ahead - either way, not new.
+            return false;
+        }
+        if !self.recv_seen.insert(seq) {
+            return false;
+        }
+        while self.recv_seen.remove(&self.recv_next) {
+            self.recv_next += 1;
+        }
+        true
+    }
+}
+
+/// Delivered to the main loop in place of a packet when a peer stops acking reliable sends -
+/// treated the same as a heartbeat timeout.
+pub enum IncomingEvent {
+    Packet(PacketTypes),
+    PeerTimedOut,
+}
+
 pub enum NetworkThreadMessage {
     Shutdown,
     Packet(SocketAddr, Vec<u8>),
+    ReliablePacket(SocketAddr, Vec<u8>),
 }
 
 pub type ToNetwork = UnboundedSender<NetworkThreadMessage>;
-pub type FromNetwork = UnboundedReceiver<(SocketAddr, PacketTypes)>;
-type ToMain = UnboundedSender<(SocketAddr, PacketTypes)>;
+pub type FromNetwork = UnboundedReceiver<(SocketAddr, IncomingEvent)>;
+pub type ToMain = UnboundedSender<(SocketAddr, IncomingEvent)>;
+
+/// Addresses of connected WebSocket game clients (see `game_ws`), each mapped to a channel that
+/// writes straight to that client's socket. Consulted by the outgoing-packet arms below before
+/// falling back to `socket.send_to`, so `process_client_packet` and everything upstream of it
+/// can address a WebSocket client by its `SocketAddr` exactly like a UDP one, oblivious to which
+/// transport it's actually on.
+pub type WsClients = Arc<std::sync::Mutex<HashMap<SocketAddr, UnboundedSender<Vec<u8>>>>>;
 
+/// Takes the socket as an `Arc` so a future caller can hand out additional clones (e.g. to a
+/// separate send-side task) without re-threading the signature again. We don't spawn anything
+/// off this socket ourselves yet: the receive buffer below is reallocated fresh each iteration
+/// instead of being reused across the loop, but `incoming_packet_handler` still runs inline,
+/// and decoded packets still reach `main.rs` one at a time through `to_main`. Fanning the handler
+/// itself out onto `tokio::spawn` per datagram would race against `peers`' per-socket ACK/dedup
+/// bookkeeping (`PeerReliability::mark_received` assumes in-order processing per peer), and
+/// `World` has no lock around it at all on the `main.rs` side - that's a bigger, cross-cutting
+/// change than a buffer/socket-ownership cleanup should carry.
+///
+/// Also returns the `ToMain` sender this thread feeds, so a second transport (`game_ws`) can
+/// push decoded packets into the same queue `main.rs` already drains from `FromNetwork`.
 pub fn init(
-    socket: UdpSocket,
+    socket: Arc<UdpSocket>,
     to_console: ToConsole,
     max_network_errors: u8,
-) -> (JoinHandle<()>, FromNetwork, ToNetwork) {
-    let (to_main, from_network) = mpsc::unbounded_channel::<(SocketAddr, PacketTypes)>();
+    metrics: Metrics,
+    ws_clients: WsClients,
+) -> (JoinHandle<()>, FromNetwork, ToNetwork, ToMain) {
+    let (to_main, from_network) = mpsc::unbounded_channel::<(SocketAddr, IncomingEvent)>();
     let (to_network, from_main) = mpsc::unbounded_channel::<NetworkThreadMessage>();
+    let to_main_returned = to_main.clone();
     let network_thread = tokio::spawn(async move {
         let (to_main, mut from_main) = (to_main, from_main);
-        let mut buf = [0u8; 1024];
         let mut network_error_strikes = 0u8;
+        let mut peers: HashMap<SocketAddr, PeerReliability> = HashMap::new();
+        let mut retransmit_tick = time::interval(RETRANSMIT_TICK);
         c_info!(
             to_console,
             "Listening on {}",
             socket.local_addr().expect("cannot get socket address")
         );
         loop {
+            let mut buf = [0u8; RECV_BUFFER_SIZE];
             tokio::select! {
                 maybe_packet_incoming = socket.recv_from(&mut buf) => {
                     match maybe_packet_incoming {
                         Ok((len, addr)) => {
-                            if let Err(e) = incoming_packet_handler(to_console.clone(), to_main.clone(), len, addr, &mut buf).await {
+                            if let Err(e) = incoming_packet_handler(to_console.clone(), to_main.clone(), &socket, &mut peers, len, addr, &mut buf, &metrics).await {
                                 c_error!(to_console, "error while handling packet: {e}");
                             }
                         },
                         Err(e) => {
                             c_error!(to_console, "Encountered a network error while trying to recieve a packet: {}", e);
                             network_error_strikes += 1;
+                            metrics.network_error_strikes.set(network_error_strikes as i64);
                             if network_error_strikes > max_network_errors {
                                 c_error!(to_console, "max_network_errors reached! shutting down.");
                                 break;
@@ -245,54 +689,212 @@ pub fn init(
                     }
                 }
                 outgoing_message = from_main.recv() => {
-                    if let Some(message) = outgoing_message {
-                        match message {
-                            NetworkThreadMessage::Shutdown => break,
-                            NetworkThreadMessage::Packet(addr, packet) => {
-                                let _ = socket.send_to(&packet, addr).await;
+                    match outgoing_message {
+                        Some(NetworkThreadMessage::Shutdown) => break,
+                        Some(NetworkThreadMessage::Packet(addr, packet)) => {
+                            // A WebSocket client's transport (TCP) already frames messages, so it
+                            // gets the raw packet bytes with none of the UDP-only framing below.
+                            let ws_sender = ws_clients.lock().unwrap().get(&addr).cloned();
+                            if let Some(ws_sender) = ws_sender {
+                                if ws_sender.send(packet.clone()).is_ok() {
+                                    metrics.packets_sent.inc();
+                                    metrics.bytes_sent.inc_by(packet.len() as u64);
+                                }
+                                continue;
+                            }
+                            let framed = frame_unreliable(&packet);
+                            if socket.send_to(&framed, addr).await.is_ok() {
+                                metrics.packets_sent.inc();
+                                metrics.bytes_sent.inc_by(framed.len() as u64);
+                            }
+                        }
+                        Some(NetworkThreadMessage::ReliablePacket(addr, packet)) => {
+                            // Same as above: TCP already guarantees delivery and order, so a
+                            // WebSocket client skips the ack/retransmit bookkeeping entirely -
+                            // that's only needed to make UDP behave like the transport WebSocket
+                            // clients get for free.
+                            let ws_sender = ws_clients.lock().unwrap().get(&addr).cloned();
+                            if let Some(ws_sender) = ws_sender {
+                                if ws_sender.send(packet.clone()).is_ok() {
+                                    metrics.packets_sent.inc();
+                                    metrics.bytes_sent.inc_by(packet.len() as u64);
+                                }
+                                continue;
+                            }
+                            let peer = peers.entry(addr).or_default();
+                            let seq = peer.next_send_seq;
+                            peer.next_send_seq += 1;
+                            let fragments = frame_reliable(seq, &packet);
+                            for fragment in &fragments {
+                                if socket.send_to(fragment, addr).await.is_ok() {
+                                    metrics.packets_sent.inc();
+                                    metrics.bytes_sent.inc_by(fragment.len() as u64);
+                                }
                             }
+                            peer.unacked.insert(seq, UnackedPacket {
+                                fragments,
+                                sent_at: Instant::now(),
+                                rto: INITIAL_RTO,
+                                retries: 0,
+                            });
                         }
+                        None => {}
+                    }
+                }
+                _ = retransmit_tick.tick() => {
+                    let mut timed_out = vec![];
+                    let now = Instant::now();
+                    for (addr, peer) in peers.iter_mut() {
+                        peer.reassembly.retain(|_, reassembly| {
+                            now.duration_since(reassembly.started_at) < REASSEMBLY_TIMEOUT
+                        });
+                        for unacked in peer.unacked.values_mut() {
+                            if now.duration_since(unacked.sent_at) < unacked.rto {
+                                continue;
+                            }
+                            if unacked.retries >= max_network_errors {
+                                timed_out.push(*addr);
+                                continue;
+                            }
+                            for fragment in &unacked.fragments {
+                                let _ = socket.send_to(fragment, *addr).await;
+                            }
+                            unacked.sent_at = now;
+                            unacked.rto = (unacked.rto * 2).min(MAX_RTO);
+                            unacked.retries += 1;
+                        }
+                    }
+                    for addr in timed_out {
+                        peers.remove(&addr);
+                        c_warn!(to_console, "Peer {addr} stopped acking reliable packets, timing it out.");
+                        let _ = to_main.send((addr, IncomingEvent::PeerTimedOut));
                     }
                 }
             }
         }
     });
-    (network_thread, from_network, to_network)
+    (network_thread, from_network, to_network, to_main_returned)
 }
 
 pub async fn incoming_packet_handler(
     to_console: ToConsole,
     to_main: ToMain,
+    socket: &UdpSocket,
+    peers: &mut HashMap<SocketAddr, PeerReliability>,
     len: usize,
     addr: SocketAddr,
     buf: &mut [u8],
+    metrics: &Metrics,
 ) -> io::Result<()> {
     //c_debug!(to_console, "{:?} bytes received from {:?}", len, addr);
 
-    let packet: serde_pickle::Result<PacketTypes> = from_slice(&buf[..len], DeOptions::new());
-    match packet {
-        Ok(packet) => {
-            let _ = to_main.send((addr, packet));
+    if buf.is_empty() || len == 0 {
+        return Ok(());
+    }
+    metrics.bytes_received.inc_by(len as u64);
+    let frame_tag = buf[0];
+
+    // Reliable frames carry a fragment of a logical message, not necessarily the whole thing -
+    // reassemble before decoding anything. Unreliable frames (including the ACKs below) are
+    // always a single whole payload, same as before fragmentation existed.
+    let payload: Vec<u8> = if frame_tag == FRAME_RELIABLE {
+        let Some((seq, fragment_index, fragment_count, chunk)) = parse_reliable_frame(&buf[..len])
+        else {
+            c_warn!(to_console, "Recieved truncated reliable frame from {}, ignoring!", addr);
+            return Ok(());
+        };
+        let chunk = chunk.to_vec();
+
+        let peer = peers.entry(addr).or_default();
+        if seq < peer.recv_next {
+            // Already fully reassembled and delivered - a duplicate fragment of a message we
+            // already acked. Re-ack so the sender stops resending it, but don't reprocess.
+            let ack = PacketTypes::ServerAck { seq }.to_bytes().unwrap_or_default();
+            let _ = socket.send_to(&frame_unreliable(&ack), addr).await;
+            return Ok(());
+        }
+        if seq >= peer.recv_next + RECV_WINDOW || fragment_count > MAX_FRAGMENTS_PER_MESSAGE {
+            // Same implausibility bound `PeerReliability::mark_received` applies to completed
+            // messages, checked here too so a peer can't pin an unbounded number of incomplete
+            // `Reassembly` entries by fragmenting messages with out-of-window or absurd sequence
+            // numbers that will never be acted on.
+            c_warn!(
+                to_console,
+                "Rejecting reliable fragment from {} with implausible seq {} / fragment_count {}",
+                addr,
+                seq,
+                fragment_count
+            );
+            return Ok(());
+        }
+
+        let reassembled = peer
+            .reassembly
+            .entry(seq)
+            .or_insert_with(|| Reassembly::new(fragment_count))
+            .receive(fragment_index, chunk);
+        let Some(payload) = reassembled else {
+            // Still waiting on the rest of this message's fragments - nothing to ack or decode
+            // yet, the sender only cares about the whole message being acked.
+            return Ok(());
+        };
+        peer.reassembly.remove(&seq);
+
+        let is_new = peer.mark_received(seq);
+        let ack = PacketTypes::ServerAck { seq }.to_bytes().unwrap_or_default();
+        let _ = socket.send_to(&frame_unreliable(&ack), addr).await;
+        if !is_new {
+            return Ok(());
         }
+        payload
+    } else {
+        buf[1..len].to_vec()
+    };
+
+    let packet: serde_pickle::Result<PacketTypes> = PacketTypes::from_bytes(&payload);
+    let packet = match packet {
+        Ok(packet) => packet,
         Err(e) => {
+            metrics.decode_failures.inc();
             c_warn!(
                 to_console,
                 "Recieved unknown packet from {}, ignoring! (Err: {:?})",
                 addr,
                 e
             );
+            return Ok(());
         }
+    };
+    metrics
+        .packets_received
+        .with_label_values(&[packet.type_name()])
+        .inc();
+
+    if let PacketTypes::ClientAck { seq } | PacketTypes::ServerAck { seq } = packet {
+        if let Some(peer) = peers.get_mut(&addr) {
+            peer.unacked.remove(&seq);
+        }
+        return Ok(());
     }
 
+    let _ = to_main.send((addr, IncomingEvent::Packet(packet)));
+
     Ok(())
 }
 
+/// Runs every [`constants::SECONDS_BETWEEN_HEARTBEATS`] (see its call site in `main.rs`). Every
+/// player still `connection_alive` gets a [`PacketTypes::ServerHeartbeat`] and is flagged not-alive;
+/// a player the client answers with `ClientHeartbeat` before the next tick gets flagged alive again
+/// (see that branch in [`process_client_packet`]). Anyone still not-alive when this runs again
+/// never answered in time and is kicked as timed out - the same two-tick "ping, then reap" shape as
+/// quectocraft's keep-alive.
 pub async fn heartbeat(
     to_console: ToConsole,
+    to_web: ToWeb,
     to_network: ToNetwork,
     world: &mut World,
+    metrics: &Metrics,
 ) -> io::Result<()> {
-    // sends a heartbeat packet to all incoming players.
     let mut inactive: Vec<u32> = vec![];
     for player in world.players.iter_mut() {
         if player.connection_alive {
@@ -308,10 +910,12 @@ pub async fn heartbeat(
             "Kicking {} players due to inactivity.",
             inactive.len()
         );
+        metrics.players_kicked_inactive.inc_by(inactive.len() as u64);
         for id in inactive {
             world
                 .kick(
                     to_console.clone(),
+                    to_web.clone(),
                     to_network.clone(),
                     id,
                     Some("Kicked due to inactivity."),
@@ -323,10 +927,14 @@ pub async fn heartbeat(
 }
 pub async fn process_client_packet(
     to_console: ToConsole,
+    to_web: ToWeb,
     to_network: ToNetwork,
     packet: PacketTypes,
     addr: SocketAddr,
     world: &mut World,
+    plugins: &Plugins,
+    commands: &mut Commands,
+    metrics: &Metrics,
 ) -> io::Result<()> {
     macro_rules! assert_player_exists {
         ($to_console: expr, $world:expr, $addr:expr, $iter:ident, $fn:ident, $player_var:ident, $block:block) => {
@@ -359,12 +967,70 @@ pub async fn process_client_packet(
         };
     }
     match packet {
-        PacketTypes::ClientHello { name } => {
+        PacketTypes::ClientHello { name, protocol_version } => {
+            if !SUPPORTED_PROTOCOLS.contains(&protocol_version) {
+                c_warn!(
+                    to_console,
+                    "Rejected join from {}: unsupported protocol version {} (server supports {:?})",
+                    addr,
+                    protocol_version,
+                    SUPPORTED_PROTOCOLS
+                );
+                encode_and_send!(
+                    to_network,
+                    PacketTypes::ServerKick {
+                        msg: format!(
+                            "Unsupported protocol version {protocol_version}: this server supports {SUPPORTED_PROTOCOLS:?}."
+                        )
+                    },
+                    addr
+                );
+                return Ok(());
+            }
+            if name.is_empty()
+                || name.len() > constants::MAX_PLAYER_NAME_LENGTH
+                || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                c_warn!(to_console, "Rejected join from {}: invalid name {:?}", addr, name);
+                encode_and_send!(
+                    to_network,
+                    PacketTypes::ServerKick {
+                        msg: format!(
+                            "Invalid name: must be 1-{} alphanumeric/underscore characters.",
+                            constants::MAX_PLAYER_NAME_LENGTH
+                        )
+                    },
+                    addr
+                );
+                return Ok(());
+            }
+            if world.players.iter().any(|p| p.name == name) {
+                c_warn!(to_console, "Rejected join from {}: name {} already taken", addr, name);
+                encode_and_send!(
+                    to_network,
+                    PacketTypes::ServerKick {
+                        msg: format!("The name `{name}` is already taken.")
+                    },
+                    addr
+                );
+                return Ok(());
+            }
+            if let Some(reason) = commands.check_ban(&name, addr.ip()) {
+                c_warn!(to_console, "Rejected join from {}: banned ({})", addr, reason);
+                encode_and_send!(
+                    to_network,
+                    PacketTypes::ServerKick {
+                        msg: format!("You are banned: {reason}")
+                    },
+                    addr
+                );
+                return Ok(());
+            }
             c_info!(to_console, "{} joined the server!", name);
             let spawn_x = world.get_spawn();
             let connection = result_unwrap_or_return_early!(
                 to_console,
-                ClientConnection::new_at(addr, world, spawn_x, name),
+                ClientConnection::new_at(addr, world, spawn_x, name, protocol_version),
                 "cannot spawn player: {}"
             );
             let spawn_block_pos = (
@@ -426,7 +1092,23 @@ pub async fn process_client_packet(
                 }
             }
 
+            let _ = to_web.send(WebEvent::PlayerJoin {
+                id: connection.id,
+                name: connection.name.clone(),
+            });
+            let plugin_actions = plugins.on_player_join(&to_console, connection.id, &connection.name);
             world.players.push(connection);
+            if let Err(e) = plugins::apply_actions(
+                to_console.clone(),
+                to_web.clone(),
+                to_network.clone(),
+                world,
+                plugin_actions,
+            )
+            .await
+            {
+                c_error!(to_console, "error applying plugin actions: {e}");
+            }
         }
         PacketTypes::ClientGoodbye {} => {
             match world.players.par_iter().position_any(|x| x.addr == addr) {
@@ -444,6 +1126,7 @@ pub async fn process_client_packet(
                         connection.name,
                         connection.addr
                     );
+                    let _ = to_web.send(WebEvent::PlayerLeave { id: connection.id });
 
                     let last_location = (
                         connection.server_player.x.round() as u32,
@@ -528,19 +1211,39 @@ pub async fn process_client_packet(
                             "cannot get block: {}"
                         );
                         if is_solid(block) {
+                            let plugin_actions = plugins.on_block_break(
+                                &to_console,
+                                world.players[idx].id,
+                                x,
+                                y,
+                                block.into(),
+                            );
+                            if let Err(e) = plugins::apply_actions(
+                                to_console.clone(),
+                                to_web.clone(),
+                                to_network.clone(),
+                                world,
+                                plugin_actions,
+                            )
+                            .await
+                            {
+                                c_error!(to_console, "error applying plugin actions: {e}");
+                            }
                             let item: Option<Item> = block.into();
                             if let Some(item) = item {
                                 let stack: ItemStack = item.into();
                                 let _ = world.players[idx].server_player.insert(stack);
+                                world.players[idx].server_player.damage_current(1);
                                 world.players[idx].server_player.notify_inventory_changed(
                                     to_network.clone(),
                                     world.players[idx].addr,
                                 );
-                                if let Err(e) = world
-                                    .set_block_and_notify(to_network.clone(), x, y, Block::Air)
+                                match world
+                                    .set_block_and_notify(to_web.clone(), to_network.clone(), x, y, Block::Air)
                                     .await
                                 {
-                                    match e {
+                                    Ok(_) => metrics.blocks_broken.inc(),
+                                    Err(e) => match e {
                                         WorldError::NetworkError(e) => {
                                             c_error!(
                                                 to_console,
@@ -548,7 +1251,7 @@ pub async fn process_client_packet(
                                             )
                                         }
                                         _ => c_error!(to_console, "error while placing block: {e}"),
-                                    }
+                                    },
                                 }
                             }
                         }
@@ -564,12 +1267,35 @@ pub async fn process_client_packet(
                                 world.players[idx].server_player.get_current_itemstack()
                             {
                                 if let Some(block) = item.item.into() {
+                                    let (allowed, plugin_actions) = plugins.on_block_place(
+                                        &to_console,
+                                        world.players[idx].id,
+                                        x,
+                                        y,
+                                        block.into(),
+                                    );
+                                    if let Err(e) = plugins::apply_actions(
+                                        to_console.clone(),
+                                        to_web.clone(),
+                                        to_network.clone(),
+                                        world,
+                                        plugin_actions,
+                                    )
+                                    .await
+                                    {
+                                        c_error!(to_console, "error applying plugin actions: {e}");
+                                    }
+                                    if !allowed {
+                                        return Ok(());
+                                    }
+
                                     let place_result = world
-                                        .set_block_and_notify(to_network.clone(), x, y, block)
+                                        .set_block_and_notify(to_web.clone(), to_network.clone(), x, y, block)
                                         .await;
 
                                     match place_result {
                                         Ok(_) => {
+                                            metrics.blocks_placed.inc();
                                             world.players[idx].server_player.consume_current();
                                             world.players[idx]
                                                 .server_player
@@ -619,10 +1345,11 @@ pub async fn process_client_packet(
                 match world.mark_chunk_loaded_by_id(chunk_coords_x, chunk_coords_y, player_conn.id)
                 {
                     Ok(chunk) => {
-                        encode_and_send!(
+                        let chunk = chunk.clone();
+                        encode_and_send_reliable!(
                             to_network,
                             PacketTypes::ServerChunkResponse {
-                                chunk: chunk.clone().into(),
+                                chunk: NetworkChunk::with_light(chunk, world),
                             },
                             addr
                         );
@@ -682,21 +1409,121 @@ pub async fn process_client_packet(
                 }
             )
         }
-        PacketTypes::ClientSendMessage { msg } => {
+        PacketTypes::ClientSendMessage { msg, target } => {
+            let mut chatter = None;
             assert_player_exists!(to_console, world, addr, par_iter, find_any, player_conn, {
-                c_info!(to_console, "[CHAT] <{}> {}", player_conn.name, msg);
-                world.players.iter().for_each(|player| {
+                chatter = Some((
+                    player_conn.id,
+                    player_conn.name.clone(),
+                    player_conn.channel.clone(),
+                    player_conn.server_player.x,
+                    player_conn.server_player.y,
+                ));
+            });
+            let (id, name, channel, x, y) = match chatter {
+                Some(chatter) => chatter,
+                None => return Ok(()),
+            };
+
+            if let Some(command_line) = msg.strip_prefix('/') {
+                match commands.dispatch(world, id, &name, command_line) {
+                    Ok(actions) => {
+                        if let Err(e) = plugins::apply_actions(
+                            to_console.clone(),
+                            to_web.clone(),
+                            to_network.clone(),
+                            world,
+                            actions,
+                        )
+                        .await
+                        {
+                            c_error!(to_console, "error applying command actions: {e}");
+                        }
+                    }
+                    Err(e) => {
+                        encode_and_send!(
+                            to_network,
+                            PacketTypes::ServerSendMessage {
+                                player_name: "Server".to_string(),
+                                player_id: 0,
+                                msg: format!("Error: {e}")
+                            },
+                            addr
+                        );
+                    }
+                }
+                return Ok(());
+            }
+
+            let (decision, plugin_actions) = plugins.on_chat(&to_console, id, &msg);
+            if let Err(e) = plugins::apply_actions(
+                to_console.clone(),
+                to_web.clone(),
+                to_network.clone(),
+                world,
+                plugin_actions,
+            )
+            .await
+            {
+                c_error!(to_console, "error applying plugin actions: {e}");
+            }
+            let Some(msg) = decision else {
+                return Ok(());
+            };
+
+            c_info!(to_console, "[CHAT] <{}> {}", name, msg);
+
+            if let Some(target_id) = target {
+                match world.players.iter().find(|p| p.id == target_id) {
+                    Some(recipient) => {
+                        encode_and_send!(
+                            to_network,
+                            PacketTypes::ServerSendMessage {
+                                player_name: name.clone(),
+                                player_id: id,
+                                msg: msg.clone()
+                            },
+                            recipient.addr
+                        );
+                    }
+                    None => {
+                        encode_and_send!(
+                            to_network,
+                            PacketTypes::ServerSendMessage {
+                                player_name: "Server".to_string(),
+                                player_id: 0,
+                                msg: "That player isn't online.".to_string()
+                            },
+                            addr
+                        );
+                    }
+                }
+                return Ok(());
+            }
+
+            world
+                .players
+                .iter()
+                .filter(|player| match &channel {
+                    ChatChannel::Global => true,
+                    ChatChannel::Local => {
+                        let (dist_x, dist_y) =
+                            (player.server_player.x - x, player.server_player.y - y);
+                        dist_x.powi(2) + dist_y.powi(2) <= constants::MAX_INTERACT_RANGE.pow(2) as f32
+                    }
+                    ChatChannel::Team(team) => player.channel == ChatChannel::Team(team.clone()),
+                })
+                .for_each(|player| {
                     encode_and_send!(
                         to_network,
                         PacketTypes::ServerSendMessage {
-                            player_name: player_conn.name.clone(),
-                            player_id: player_conn.id,
+                            player_name: name.clone(),
+                            player_id: id,
                             msg: msg.clone()
                         },
                         player.addr
                     );
                 });
-            })
         }
         PacketTypes::ClientTryAttack { player_id } => {
             let attacker_idx = option_unwrap_or_return_early!(
@@ -716,31 +1543,88 @@ pub async fn process_client_packet(
                 attacker.server_player.y - attacked.server_player.y,
             );
             if dist_x.powi(2) + dist_y.powi(2) <= constants::MAX_INTERACT_RANGE.pow(2) as f32 {
-                let mut attacked = attacked.clone();
+                let attacker_id = attacker.id;
                 let damage = attacker.server_player.get_current_damage();
-                attacked.server_player.health -= damage;
-                encode_and_send!(
-                    to_network,
-                    PacketTypes::ServerUpdateHealth {
-                        health: attacked.server_player.health
-                    },
-                    attacked.addr
-                );
+                let health_after = attacked.server_player.health - damage;
+
+                let (allowed, plugin_actions) = plugins.on_attack(&to_console, attacker_id, player_id);
+                if let Err(e) = plugins::apply_actions(
+                    to_console.clone(),
+                    to_web.clone(),
+                    to_network.clone(),
+                    world,
+                    plugin_actions,
+                )
+                .await
+                {
+                    c_error!(to_console, "error applying plugin actions: {e}");
+                }
+                if !allowed {
+                    return Ok(());
+                }
+
+                world.players[attacker_idx].server_player.damage_current(1);
+                world.players[attacker_idx]
+                    .server_player
+                    .notify_inventory_changed(to_network.clone(), world.players[attacker_idx].addr);
 
                 let magnitude = (dist_x.powi(2) + dist_y.powi(2)).sqrt();
                 let (norm_x, norm_y) = (dist_x / magnitude, dist_y / magnitude);
-                attacked.server_player.acceleration.x = norm_x * constants::KNOCKBACK_POWER;
-                attacked.server_player.acceleration.y = norm_y * constants::KNOCKBACK_POWER;
-
-                world.players[attacked_idx] = attacked;
+                updates::apply_updates(
+                    to_network.clone(),
+                    world,
+                    vec![
+                        Update::HealthChanged {
+                            player_id,
+                            health: health_after,
+                        },
+                        Update::Knockback {
+                            player_id,
+                            x: norm_x * constants::KNOCKBACK_POWER,
+                            y: norm_y * constants::KNOCKBACK_POWER,
+                        },
+                    ],
+                )
+                .await?;
             }
         }
         PacketTypes::ClientChangeSlot { slot } => {
             assert_player_exists!(to_console, world, addr, par_iter, position_any, idx, {
-                world.players[idx].server_player.selected_slot = slot;
+                let id = world.players[idx].id;
+                let (allowed, plugin_actions) = plugins.on_change_slot(&to_console, id, slot);
+                if let Err(e) = plugins::apply_actions(
+                    to_console.clone(),
+                    to_web.clone(),
+                    to_network.clone(),
+                    world,
+                    plugin_actions,
+                )
+                .await
+                {
+                    c_error!(to_console, "error applying plugin actions: {e}");
+                }
+                if allowed {
+                    world.players[idx].server_player.selected_slot = slot;
+                }
+            })
+        }
+        PacketTypes::ClientRequestCraft { item } => {
+            assert_player_exists!(to_console, world, addr, par_iter, position_any, idx, {
+                let id = world.players[idx].id;
+                let plugin_actions = plugins.on_craft(&to_console, id, item);
+                if let Err(e) = plugins::apply_actions(
+                    to_console.clone(),
+                    to_web.clone(),
+                    to_network.clone(),
+                    world,
+                    plugin_actions,
+                )
+                .await
+                {
+                    c_error!(to_console, "error applying plugin actions: {e}");
+                }
             })
         }
-        PacketTypes::ClientRequestCraft { item } => {}
 
         _ => {
             c_error!(
@@ -753,3 +1637,142 @@ pub async fn process_client_packet(
 
     Ok(())
 }
+
+/// An in-process harness for the wire protocol: binds `network::init` to a real loopback UDP
+/// socket, drives it with the same `process_client_packet` dispatch `main.rs`'s `from_network`
+/// arm uses, and pokes it with a second socket acting as a client - using the same
+/// `frame_unreliable`/`PacketTypes::to_bytes`/`PacketTypes::from_bytes` helpers a real client
+/// would, instead of duplicating the framing by hand. Exercises the framing/ack path end to end
+/// rather than just unit-testing `frame_reliable`/`parse_reliable_frame` in isolation.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chat_commands::Commands;
+    use crate::console;
+    use crate::plugins::Plugins;
+    use std::num::NonZeroU32;
+    use std::path::Path;
+
+    /// A tiny empty world, good enough to drive a real handshake without paying for terrain
+    /// generation.
+    fn test_world(to_console: ToConsole) -> World {
+        World::generate(
+            to_console,
+            16,
+            16,
+            16,
+            8,
+            NonZeroU32::new(4).unwrap(),
+            crate::WorldType::Empty,
+        )
+        .expect("test world generation")
+    }
+
+    /// Spawns a real `network::init` on a loopback socket, plus a minimal stand-in for `main.rs`'s
+    /// own `from_network.recv()` arm that dispatches every decoded packet to `process_client_packet`
+    /// against a real `World` - everything else in `main`'s loop (ticks, console commands,
+    /// autosave, ...) has nothing to do with the wire protocol this is testing, so it's left out.
+    async fn spawn_test_server() -> SocketAddr {
+        let socket = Arc::new(UdpSocket::bind("127.0.0.1:0").await.expect("bind test socket"));
+        let addr = socket.local_addr().expect("test socket address");
+        let (_console_thread, _from_console, to_console, _) = console::init(false, false);
+        let to_web = crate::web::channel();
+        let metrics = Metrics::new().expect("test metrics");
+        let ws_clients: WsClients = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let (_network_thread, mut from_network, to_network, _) =
+            init(socket, to_console.clone(), 3, metrics.clone(), ws_clients);
+
+        tokio::spawn(async move {
+            let mut world = test_world(to_console.clone());
+            let plugins = Plugins::load(Path::new("yourcraft-test-plugins-does-not-exist"), to_console.clone())
+                .expect("empty plugins dir");
+            let mut commands = Commands::new();
+            while let Some((addr, event)) = from_network.recv().await {
+                if let IncomingEvent::Packet(packet) = event {
+                    let _ = process_client_packet(
+                        to_console.clone(),
+                        to_web.clone(),
+                        to_network.clone(),
+                        packet,
+                        addr,
+                        &mut world,
+                        &plugins,
+                        &mut commands,
+                        &metrics,
+                    )
+                    .await;
+                }
+            }
+        });
+
+        addr
+    }
+
+    /// `network_utils`: encodes `packet` the same way a real client would and sends it
+    /// unreliably to `server_addr`, reusing [`frame_unreliable`] and [`PacketTypes::to_bytes`]
+    /// instead of duplicating the wire format.
+    async fn bot_send(bot: &UdpSocket, server_addr: SocketAddr, packet: PacketTypes) {
+        let bytes = packet.to_bytes().expect("encode test packet");
+        bot.send_to(&frame_unreliable(&bytes), server_addr)
+            .await
+            .expect("send test packet");
+    }
+
+    /// `network_utils`: receives one unreliable reply frame and decodes it back into a
+    /// [`PacketTypes`] via [`PacketTypes::from_bytes`].
+    async fn bot_recv(bot: &UdpSocket) -> PacketTypes {
+        let mut buf = [0u8; RECV_BUFFER_SIZE];
+        let (len, _) = bot.recv_from(&mut buf).await.expect("recv test packet");
+        assert_eq!(buf[0], FRAME_UNRELIABLE, "expected an unreliable reply frame");
+        PacketTypes::from_bytes(&buf[1..len]).expect("decode test packet")
+    }
+
+    #[tokio::test]
+    async fn hello_handshake_gets_a_server_sync() {
+        let server_addr = spawn_test_server().await;
+        let bot = UdpSocket::bind("127.0.0.1:0").await.expect("bind bot socket");
+
+        bot_send(
+            &bot,
+            server_addr,
+            PacketTypes::ClientHello {
+                name: "testbot".to_string(),
+                protocol_version: PROTOCOL_VERSION,
+            },
+        )
+        .await;
+
+        match bot_recv(&bot).await {
+            PacketTypes::ServerSync {
+                world_width,
+                world_height,
+                chunk_size,
+                ..
+            } => {
+                assert_eq!((world_width, world_height, chunk_size), (16, 16, 16));
+            }
+            other => panic!("expected ServerSync, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn unsupported_protocol_version_gets_kicked() {
+        let server_addr = spawn_test_server().await;
+        let bot = UdpSocket::bind("127.0.0.1:0").await.expect("bind bot socket");
+
+        bot_send(
+            &bot,
+            server_addr,
+            PacketTypes::ClientHello {
+                name: "testbot".to_string(),
+                protocol_version: PROTOCOL_VERSION + 1,
+            },
+        )
+        .await;
+
+        match bot_recv(&bot).await {
+            PacketTypes::ServerKick { .. } => {}
+            other => panic!("expected ServerKick, got {other:?}"),
+        }
+    }
+}
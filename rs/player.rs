@@ -1,9 +1,10 @@
 use std::{cmp::Ordering, net::SocketAddr, num::NonZeroU8};
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 
 use crate::{
     constants,
     network::{PacketTypes, ToNetwork},
-    world::{is_solid, Block, BlockPos, World, WorldError},
+    world::{hardness, is_solid, Block, BlockPos, World, WorldError},
 };
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct Velocity {
@@ -97,13 +98,49 @@ enum Shift {
     Right,
 }
 
+/// What a player's `upper_body`/`lower_body` cells are occupied by, for the handful of blocks
+/// that change how movement works rather than just blocking it outright. Checked in priority
+/// order by [`Medium::of`] - a ladder submerged in lava is climbable, not scalding.
+#[derive(PartialEq, Clone, Copy)]
+enum Medium {
+    Air,
+    Water,
+    Lava,
+    Ladder,
+}
+
+impl Medium {
+    fn of(surrounding: Surrounding) -> Self {
+        let Surrounding {
+            upper_body,
+            lower_body,
+            ..
+        } = surrounding;
+        let touching = |block: Block| {
+            [upper_body, lower_body]
+                .into_iter()
+                .flatten()
+                .any(|(_, _, bl)| bl == block)
+        };
+        if touching(Block::Ladder) {
+            Medium::Ladder
+        } else if touching(Block::Lava) {
+            Medium::Lava
+        } else if touching(Block::Water) {
+            Medium::Water
+        } else {
+            Medium::Air
+        }
+    }
+}
+
 impl Player {
     pub fn spawn_at(world: &World, x: u32) -> Result<Self, WorldError> {
         let (highest_x, highest_y) = world.get_highest_block_at(x)?;
         Ok(Player {
             x: highest_x as f32,
             y: (highest_y + 1) as f32,
-            health: 5.0,
+            health: constants::MAX_HEALTH,
             hitbox_width: constants::HITBOX_WIDTH,
             hitbox_height: constants::HITBOX_HEIGHT,
             velocity: Velocity::default(),
@@ -248,42 +285,22 @@ impl Player {
         }
     }
 
-    pub fn insert(&mut self, itemstack: ItemStack) -> Result<(), u8> {
-        let mut count_left = itemstack.count.get();
-        for stack in self.inventory.iter_mut() {
-            if count_left == 0 {
-                return Ok(());
-            }
-            match stack {
-                None => {
-                    *stack = Some(ItemStack {
-                        item: itemstack.item,
-                        count: NonZeroU8::new(count_left).unwrap_or_else(|| unreachable!()),
-                    });
-                    count_left = 0;
-                }
-                Some(stack) => {
-                    if stack.item == itemstack.item {
-                        match stack.count.checked_add(count_left) {
-                            Some(c) => {
-                                stack.count = c;
-                                count_left = 0;
-                            }
-                            None => {
-                                count_left = stack.count.get().wrapping_add(count_left + 1);
-                                stack.count =
-                                    NonZeroU8::new(u8::MAX).unwrap_or_else(|| unreachable!());
-                            }
-                        }
-                    }
-                }
-            }
+    /// Wears down the currently selected item stack by `amount` durability, clearing the slot if
+    /// it breaks. Items with no `max_durability` (blocks, buckets, ...) are left untouched - see
+    /// [`ItemStack::damage`]. Unlike [`Player::consume_current`] (which counts *down* how many of
+    /// an item are left), this counts *up* how much wear a single tool has taken.
+    pub fn damage_current(&mut self, amount: u16) {
+        if let Some(current) = self.inventory[self.selected_slot as usize] {
+            self.inventory[self.selected_slot as usize] = current.damage(amount);
         }
-        Err(count_left)
+    }
+
+    pub fn insert(&mut self, itemstack: ItemStack) -> Result<(), u8> {
+        insert_into(&mut self.inventory, itemstack)
     }
 
     pub fn notify_inventory_changed(&self, to_network: ToNetwork, addr: SocketAddr) {
-        encode_and_send!(
+        encode_and_send_reliable!(
             to_network,
             PacketTypes::ServerUpdateInventory {
                 inv: self
@@ -313,9 +330,27 @@ impl Player {
     }
 
     fn do_fall(mut self, surrounding: Surrounding) -> Self {
+        let medium = Medium::of(surrounding);
+        if medium == Medium::Ladder {
+            // Climbing suppresses gravity entirely - `do_move`'s jump handling drives the actual
+            // vertical movement, one tick at a time, while a ladder block stays in reach.
+            self.velocity.y = 0.0;
+            self.acceleration.y = 0.0;
+            return self;
+        }
+        if medium == Medium::Water && self.do_jump {
+            // `do_move`'s `Medium::Water` jump arm already set this tick's swim velocity/
+            // acceleration and moved `self.y` itself - same reason as the `Ladder` return above,
+            // skip the generic is_grounded path here so it isn't moved a second time.
+            return self;
+        }
         match !Self::is_grounded(self.x, self.y, surrounding) {
             true => {
-                self.velocity.y = self.velocity.y.max(-constants::TERMINAL_VELOCITY);
+                let terminal_velocity = match medium {
+                    Medium::Water => constants::WATER_TERMINAL_VELOCITY,
+                    _ => constants::TERMINAL_VELOCITY,
+                };
+                self.velocity.y = self.velocity.y.max(-terminal_velocity);
                 self.y += self.velocity.y;
                 self.acceleration.y -= constants::G - Self::get_resistance(surrounding);
             }
@@ -323,22 +358,17 @@ impl Player {
                 (self.velocity.y, self.acceleration.y) = (0.0, 0.0);
             }
         }
+        if medium == Medium::Lava {
+            self.health -= constants::LAVA_DAMAGE_PER_TICK;
+        }
         self
     }
 
     fn get_resistance(surrounding: Surrounding) -> f32 {
-        let Surrounding {
-            upper_body,
-            lower_body,
-            ..
-        } = surrounding;
-        let in_water = [upper_body, lower_body]
-            .into_iter()
-            .flatten()
-            .any(|(_, _, bl)| bl == Block::Water);
-        match in_water {
-            true => constants::WATER_RESISTANCE,
-            false => constants::AIR_RESISTANCE,
+        match Medium::of(surrounding) {
+            Medium::Water => constants::WATER_RESISTANCE,
+            Medium::Lava => constants::LAVA_RESISTANCE,
+            Medium::Ladder | Medium::Air => constants::AIR_RESISTANCE,
         }
     }
 
@@ -358,15 +388,40 @@ impl Player {
     }
 
     pub fn do_move(mut self, surrounding: Surrounding) -> (Self, bool) {
-        // jump
-        if self.do_jump && Self::is_grounded(self.x, self.y, surrounding) {
-            self.acceleration.y = constants::INITIAL_JUMP_ACCEL - Self::get_resistance(surrounding);
-            self.velocity.y = constants::INITIAL_JUMP_SPEED;
-            self.y += self.velocity.y;
+        // jump, swim, or climb, depending on what the player's standing in
+        match Medium::of(surrounding) {
+            Medium::Ladder if self.do_jump => {
+                // A fixed climb speed instead of an accelerating jump arc. There's no downward
+                // input in the protocol yet to symmetrically descend a ladder - for now reaching
+                // one just stops the player from falling (see `do_fall`), and holding jump climbs
+                // up at `CLIMB_SPEED`.
+                self.velocity.y = constants::CLIMB_SPEED;
+                self.y += self.velocity.y;
+            }
+            Medium::Water if self.do_jump => {
+                // A sustained swim push rather than a one-shot jump impulse, so holding jump keeps
+                // carrying the player toward the surface instead of arcing back down like on land.
+                // `self.y` is advanced here, same as the Ladder arm above - `do_fall` short-circuits
+                // for a swimming, jump-holding player the same way it does for Ladder, so this is
+                // the only place that moves `self.y` this tick (no is_grounded double-move).
+                self.acceleration.y = constants::SWIM_ACCEL - Self::get_resistance(surrounding);
+                self.velocity.y = self.velocity.y.max(0.0) + constants::SWIM_ACCEL;
+                self.y += self.velocity.y;
+            }
+            _ if self.do_jump && Self::is_grounded(self.x, self.y, surrounding) => {
+                self.acceleration.y =
+                    constants::INITIAL_JUMP_ACCEL - Self::get_resistance(surrounding);
+                self.velocity.y = constants::INITIAL_JUMP_SPEED;
+                self.y += self.velocity.y;
+            }
+            _ => (),
         }
-        self.do_jump = false;
 
+        // `do_fall` needs to see this tick's `do_jump` (to short-circuit its generic is_grounded
+        // path for a swimming `Medium::Water` player the same way it already does for `Ladder`),
+        // so the reset is deferred until after it runs instead of happening right above.
         self = self.do_fall(surrounding);
+        self.do_jump = false;
         self = self.do_air_resistance(surrounding);
         // void check
         if self.y <= constants::RESPAWN_THRESHOLD {
@@ -385,10 +440,47 @@ impl Player {
     }
 }
 
+fn insert_into(slots: &mut [Option<ItemStack>; 9], itemstack: ItemStack) -> Result<(), u8> {
+    let mut count_left = itemstack.count.get();
+    for stack in slots.iter_mut() {
+        if count_left == 0 {
+            return Ok(());
+        }
+        match stack {
+            None => {
+                *stack = Some(ItemStack {
+                    item: itemstack.item,
+                    count: NonZeroU8::new(count_left).unwrap_or_else(|| unreachable!()),
+                    damage: itemstack.damage,
+                });
+                count_left = 0;
+            }
+            Some(stack) => {
+                if stack.item == itemstack.item {
+                    match stack.count.checked_add(count_left) {
+                        Some(c) => {
+                            stack.count = c;
+                            count_left = 0;
+                        }
+                        None => {
+                            count_left = stack.count.get().wrapping_add(count_left + 1);
+                            stack.count = NonZeroU8::new(u8::MAX).unwrap_or_else(|| unreachable!());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Err(count_left)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ItemStack {
     pub item: Item,
     pub count: NonZeroU8,
+    /// Accumulated durability damage, counting up from `0`. Only meaningful for items with a
+    /// nonzero [`Item::max_durability`] - see [`ItemStack::damage`].
+    pub damage: u16,
 }
 
 impl From<Item> for ItemStack {
@@ -396,6 +488,7 @@ impl From<Item> for ItemStack {
         Self {
             item,
             count: NonZeroU8::new(1).unwrap_or_else(|| unreachable!()),
+            damage: 0,
         }
     }
 }
@@ -404,11 +497,51 @@ impl ItemStack {
         self.count = count;
         self
     }
+
+    /// Applies `amount` durability damage, returning `None` once it reaches the item's
+    /// [`Item::max_durability`] so the caller can clear the slot. Items with no max durability
+    /// (blocks, buckets, ...) never take damage.
+    pub fn damage(mut self, amount: u16) -> Option<Self> {
+        let max = self.item.max_durability();
+        if max == 0 {
+            return Some(self);
+        }
+        self.damage = self.damage.saturating_add(amount);
+        if self.damage >= max {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+/// A named inventory not attached to any connection's `server_player`, e.g. a shared creative or
+/// admin inventory. Optionally `bind`ed to a single player id, so [`World::give_detached`] only
+/// has to serialize and send it to that one owner rather than broadcasting it to every player.
+#[derive(Debug, Clone)]
+pub struct DetachedInventory {
+    pub name: String,
+    pub slots: [Option<ItemStack>; 9],
+    pub owner: Option<u32>,
+}
+
+impl DetachedInventory {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            slots: [None; 9],
+            owner: None,
+        }
+    }
+
+    pub fn insert(&mut self, itemstack: ItemStack) -> Result<(), u8> {
+        insert_into(&mut self.slots, itemstack)
+    }
 }
 
 macro_rules! define_items {
-    ($($name:ident = ($id:expr, $block_match:expr, $breaking_power: expr)),* $(,)?) => {
-        #[derive(Debug, Clone, Copy, PartialEq)]
+    ($($name:ident = ($id:expr, $block_match:expr, $breaking_power: expr, $max_durability: expr)),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, EnumString, EnumIter)]
         pub enum Item {
             $($name = $id),*
         }
@@ -423,11 +556,19 @@ macro_rules! define_items {
         }
 
         impl Item {
-            fn breaking_power(&self) -> u8 {
+            pub fn breaking_power(&self) -> u8 {
                 match self {
                     $(Item::$name => $breaking_power),*,
                 }
             }
+
+            /// How much durability damage this item can take before it breaks, or `0` if it's
+            /// not a damageable item (blocks, buckets, ...) - see [`ItemStack::damage`].
+            pub fn max_durability(&self) -> u16 {
+                match self {
+                    $(Item::$name => $max_durability),*,
+                }
+            }
         }
 
         impl From<Item> for u8 {
@@ -445,17 +586,45 @@ macro_rules! define_items {
 }
 
 define_items! {
-    Grass = (0, Some(Block::Grass), 0),
-    Stone = (1, Some(Block::Stone), 0),
-    Wood = (2, Some(Block::Wood), 0),
-    Leaves = (3, Some(Block::Leaves), 0),
-    Bucket = (4, None, 0),
-    WaterBucket = (5, Some(Block::Water), 0),
-    WoodPickaxe = (6, None, 1),
-    WoodAxe = (7, None, 1),
-    WoodSword = (8, None, 0),
-    Ore = (9, Some(Block::Ore), 0),
-    OrePickaxe = (10, None, 2),
-    OreAxe = (11, None, 2),
-    OreSword = (12, None, 0)
+    Grass = (0, Some(Block::Grass), 0, 0),
+    Stone = (1, Some(Block::Stone), 0, 0),
+    Wood = (2, Some(Block::Wood), 0, 0),
+    Leaves = (3, Some(Block::Leaves), 0, 0),
+    Bucket = (4, None, 0, 0),
+    WaterBucket = (5, Some(Block::Water), 0, 0),
+    WoodPickaxe = (6, None, 1, 60),
+    WoodAxe = (7, None, 1, 60),
+    WoodSword = (8, None, 0, 60),
+    Ore = (9, Some(Block::Ore), 0, 0),
+    OrePickaxe = (10, None, 2, 130),
+    OreAxe = (11, None, 2, 130),
+    OreSword = (12, None, 0, 130)
+}
+
+/// Everything [`Item::info`] can report about an item: its numeric id, the block it places (if
+/// any), its mining power, and which blocks that power is enough to mine. There's no per-item
+/// stack size to report since every item shares the same cap (`u8::MAX`, enforced by
+/// [`ItemStack`]'s `NonZeroU8` count).
+#[derive(Debug, Clone)]
+pub struct ItemInfo {
+    pub id: u8,
+    pub places: Option<Block>,
+    pub breaking_power: u8,
+    pub can_mine: Vec<Block>,
+}
+
+impl Item {
+    /// Looks up this item's id, block, and mining relationships from the game's own item/block
+    /// tables (there's no external data source to query - these few items and blocks are the
+    /// whole list).
+    pub fn info(self) -> ItemInfo {
+        ItemInfo {
+            id: self.into(),
+            places: self.into(),
+            breaking_power: self.breaking_power(),
+            can_mine: Block::iter()
+                .filter(|&block| is_solid(block) && hardness(block) <= self.breaking_power())
+                .collect(),
+        }
+    }
 }
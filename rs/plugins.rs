@@ -0,0 +1,439 @@
+use crate::console::ToConsole;
+use crate::constants;
+use crate::network::{ChatChannel, ToNetwork};
+use crate::player::{Acceleration, Item, ItemStack, Player, Velocity};
+use crate::web::ToWeb;
+use crate::world::World;
+use crate::{c_error, c_info, encode_and_send};
+use mlua::{Lua, Value};
+use std::cell::RefCell;
+use std::io;
+use std::num::NonZeroU8;
+use std::path::Path;
+use std::rc::Rc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error("error reading plugin directory: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Metadata a plugin declares about itself via a top-level `meta` table (`{id=..., name=...,
+/// version=...}`). Used only for logging - plugins aren't namespaced or able to depend on
+/// each other.
+#[derive(Debug, Clone)]
+pub struct PluginMeta {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// A request queued by a plugin's `server.*` callback, to be applied by the caller once the hook
+/// that triggered it returns. Plugins never get direct access to `World` - handing out a live
+/// `&mut World` across the Lua FFI boundary would mean either `unsafe` or wrapping the whole
+/// world in `Rc<RefCell<_>>`, so instead `server.*` functions just record what they'd like to
+/// happen and the hook call sites (in [`crate::network::process_client_packet`] and the main
+/// loop's `world_tick` arm) apply it with the `World`/`ToNetwork`/`ToWeb` handles they already
+/// have in scope.
+///
+/// [`crate::chat_commands::Commands`] handlers build the same vocabulary for the same reason -
+/// a chat command shouldn't need its own, separate way of teleporting or kicking a player.
+#[derive(Debug, Clone)]
+pub enum PluginAction {
+    SendChat(String),
+    /// Like `SendChat`, but delivered only to `player_id` - used for command errors and replies
+    /// that shouldn't be broadcast to everyone.
+    SendChatTo { player_id: u32, msg: String },
+    SetBlock { x: u32, y: u32, block: u8 },
+    Kick { player_id: u32, msg: String },
+    Teleport { player_id: u32, x: f32, y: f32 },
+    Respawn { player_id: u32 },
+    GiveItem { player_id: u32, item: Item, count: NonZeroU8 },
+    /// Sets `player_id`'s health directly (clamped to `0.0..=MAX_HEALTH`), used by `/heal` and
+    /// `/kill` - unlike combat damage this doesn't go through knockback or `get_current_damage`.
+    SetHealth { player_id: u32, health: f32 },
+    /// Switches which [`ChatChannel`] `player_id` sends/receives on, used by `/channel`.
+    SetChannel { player_id: u32, channel: ChatChannel },
+}
+
+struct Plugin {
+    meta: PluginMeta,
+    lua: Lua,
+    actions: Rc<RefCell<Vec<PluginAction>>>,
+}
+
+/// The loaded set of `.lua` plugins, each sandboxed in its own [`Lua`] instance. Every hook is
+/// best-effort: a plugin that errors or doesn't define a given callback is simply skipped for
+/// that hook, logged but never fatal to the server.
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+}
+
+impl Plugins {
+    /// Loads every `*.lua` file directly inside `dir` as a plugin. A plugin that fails to parse,
+    /// run, or set up its `server` callback table is logged and skipped - one broken script
+    /// shouldn't stop the others, or the server, from starting.
+    pub fn load(dir: &Path, to_console: ToConsole) -> Result<Plugins, PluginError> {
+        let mut plugins = vec![];
+        if !dir.exists() {
+            return Ok(Plugins { plugins });
+        }
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("lua") {
+                continue;
+            }
+            match load_one(&path) {
+                Ok(plugin) => {
+                    c_info!(
+                        to_console,
+                        "Loaded plugin '{}' ({} v{}) from {}",
+                        plugin.meta.name,
+                        plugin.meta.id,
+                        plugin.meta.version,
+                        path.display()
+                    );
+                    plugins.push(plugin);
+                }
+                Err(e) => c_error!(
+                    to_console,
+                    "Failed to load plugin {}: {e}",
+                    path.display()
+                ),
+            }
+        }
+        Ok(Plugins { plugins })
+    }
+
+    /// Calls every plugin's `on_player_join(id, name)`, if defined.
+    pub fn on_player_join(&self, to_console: &ToConsole, id: u32, name: &str) -> Vec<PluginAction> {
+        self.call_each(to_console, "on_player_join", |lua| {
+            lua.globals().get::<_, mlua::Function>("on_player_join")?.call((id, name))
+        })
+    }
+
+    /// Calls every plugin's `on_block_place(id, x, y, block)`, if defined. Returns `true` (allow
+    /// the placement) unless at least one plugin explicitly returns `false` to veto it.
+    pub fn on_block_place(
+        &self,
+        to_console: &ToConsole,
+        id: u32,
+        x: u32,
+        y: u32,
+        block: u8,
+    ) -> (bool, Vec<PluginAction>) {
+        let mut allowed = true;
+        let actions = self.call_each(to_console, "on_block_place", |lua| {
+            let result: Value = lua
+                .globals()
+                .get::<_, mlua::Function>("on_block_place")?
+                .call((id, x, y, block))?;
+            if matches!(result, Value::Boolean(false)) {
+                allowed = false;
+            }
+            Ok(())
+        });
+        (allowed, actions)
+    }
+
+    /// Calls every plugin's `on_block_break(id, x, y, block)`, if defined.
+    pub fn on_block_break(
+        &self,
+        to_console: &ToConsole,
+        id: u32,
+        x: u32,
+        y: u32,
+        block: u8,
+    ) -> Vec<PluginAction> {
+        self.call_each(to_console, "on_block_break", |lua| {
+            lua.globals()
+                .get::<_, mlua::Function>("on_block_break")?
+                .call((id, x, y, block))
+        })
+    }
+
+    /// Calls every plugin's `on_chat(id, msg)`, if defined, before the message is broadcast.
+    /// Returning `false` cancels the message outright; returning a string rewrites it (seen by
+    /// later plugins in the chain too). Returns `None` if the message ended up cancelled,
+    /// otherwise `Some` of the (possibly rewritten) text to actually send.
+    pub fn on_chat(&self, to_console: &ToConsole, id: u32, msg: &str) -> (Option<String>, Vec<PluginAction>) {
+        let mut msg = msg.to_string();
+        let mut cancelled = false;
+        let actions = self.call_each(to_console, "on_chat", |lua| {
+            let result: Value = lua
+                .globals()
+                .get::<_, mlua::Function>("on_chat")?
+                .call((id, msg.clone()))?;
+            match result {
+                Value::Boolean(false) => cancelled = true,
+                Value::String(s) => msg = s.to_str()?.to_string(),
+                _ => {}
+            }
+            Ok(())
+        });
+        (if cancelled { None } else { Some(msg) }, actions)
+    }
+
+    /// Calls every plugin's `on_attack(attacker_id, target_id)`, if defined. Returns `true` (allow
+    /// the attack) unless at least one plugin explicitly returns `false` to veto it, same as
+    /// [`Self::on_block_place`].
+    pub fn on_attack(&self, to_console: &ToConsole, attacker_id: u32, target_id: u32) -> (bool, Vec<PluginAction>) {
+        let mut allowed = true;
+        let actions = self.call_each(to_console, "on_attack", |lua| {
+            let result: Value = lua
+                .globals()
+                .get::<_, mlua::Function>("on_attack")?
+                .call((attacker_id, target_id))?;
+            if matches!(result, Value::Boolean(false)) {
+                allowed = false;
+            }
+            Ok(())
+        });
+        (allowed, actions)
+    }
+
+    /// Calls every plugin's `on_change_slot(id, slot)`, if defined. Returns `true` (allow the
+    /// slot change) unless at least one plugin explicitly returns `false` to veto it.
+    pub fn on_change_slot(&self, to_console: &ToConsole, id: u32, slot: u8) -> (bool, Vec<PluginAction>) {
+        let mut allowed = true;
+        let actions = self.call_each(to_console, "on_change_slot", |lua| {
+            let result: Value = lua
+                .globals()
+                .get::<_, mlua::Function>("on_change_slot")?
+                .call((id, slot))?;
+            if matches!(result, Value::Boolean(false)) {
+                allowed = false;
+            }
+            Ok(())
+        });
+        (allowed, actions)
+    }
+
+    /// Calls every plugin's `on_craft(id, item)`, if defined. There's no built-in crafting logic
+    /// to veto here - a plugin defining this hook IS the crafting system, queuing whatever
+    /// [`PluginAction`]s (typically [`PluginAction::GiveItem`]) the recipe produces.
+    pub fn on_craft(&self, to_console: &ToConsole, id: u32, item: u8) -> Vec<PluginAction> {
+        self.call_each(to_console, "on_craft", |lua| {
+            lua.globals().get::<_, mlua::Function>("on_craft")?.call((id, item))
+        })
+    }
+
+    /// Calls every plugin's `on_tick()`, if defined.
+    pub fn on_tick(&self, to_console: &ToConsole) -> Vec<PluginAction> {
+        self.call_each(to_console, "on_tick", |lua| {
+            lua.globals().get::<_, mlua::Function>("on_tick")?.call(())
+        })
+    }
+
+    /// Runs `f` against every plugin that defines `hook_name`, draining and collecting whatever
+    /// `server.*` actions it queued. Plugins that don't define the hook, or whose call errors,
+    /// are silently skipped (the error is still logged).
+    fn call_each(
+        &self,
+        to_console: &ToConsole,
+        hook_name: &str,
+        mut f: impl FnMut(&Lua) -> mlua::Result<()>,
+    ) -> Vec<PluginAction> {
+        let mut actions = vec![];
+        for plugin in &self.plugins {
+            if !matches!(plugin.lua.globals().get(hook_name), Ok(Value::Function(_))) {
+                continue;
+            }
+            if let Err(e) = f(&plugin.lua) {
+                c_error!(
+                    to_console,
+                    "plugin '{}' errored in {hook_name}: {e}",
+                    plugin.meta.name
+                );
+            }
+            actions.append(&mut plugin.actions.borrow_mut());
+        }
+        actions
+    }
+}
+
+fn load_one(path: &Path) -> mlua::Result<Plugin> {
+    let source = std::fs::read_to_string(path)?;
+    let lua = Lua::new();
+    let actions: Rc<RefCell<Vec<PluginAction>>> = Rc::new(RefCell::new(vec![]));
+
+    let server = lua.create_table()?;
+    {
+        let actions = actions.clone();
+        server.set(
+            "send_chat",
+            lua.create_function(move |_, msg: String| {
+                actions.borrow_mut().push(PluginAction::SendChat(msg));
+                Ok(())
+            })?,
+        )?;
+    }
+    {
+        let actions = actions.clone();
+        server.set(
+            "set_block",
+            lua.create_function(move |_, (x, y, block): (u32, u32, u8)| {
+                actions.borrow_mut().push(PluginAction::SetBlock { x, y, block });
+                Ok(())
+            })?,
+        )?;
+    }
+    {
+        let actions = actions.clone();
+        server.set(
+            "kick",
+            lua.create_function(move |_, (player_id, msg): (u32, String)| {
+                actions.borrow_mut().push(PluginAction::Kick { player_id, msg });
+                Ok(())
+            })?,
+        )?;
+    }
+    lua.globals().set("server", server)?;
+
+    lua.load(&source).set_name(path.to_string_lossy()).exec()?;
+
+    let meta: mlua::Table = lua.globals().get("meta")?;
+    let meta = PluginMeta {
+        id: meta.get("id")?,
+        name: meta.get("name")?,
+        version: meta.get("version")?,
+    };
+
+    Ok(Plugin { meta, lua, actions })
+}
+
+/// Applies the [`PluginAction`]s a hook call (or a [`crate::chat_commands::Commands`] dispatch)
+/// queued: broadcasts or directs chat, sets blocks, teleports/respawns/gives items to players,
+/// and kicks players, using whatever machinery the rest of the server already uses for each.
+pub async fn apply_actions(
+    to_console: ToConsole,
+    to_web: ToWeb,
+    to_network: ToNetwork,
+    world: &mut World,
+    actions: Vec<PluginAction>,
+) -> io::Result<()> {
+    for action in actions {
+        match action {
+            PluginAction::SendChat(msg) => {
+                for player in world.players.iter() {
+                    encode_and_send!(
+                        to_network,
+                        crate::network::PacketTypes::ServerSendMessage {
+                            player_name: "Server".to_string(),
+                            player_id: 0,
+                            msg: msg.clone(),
+                        },
+                        player.addr
+                    );
+                }
+            }
+            PluginAction::SendChatTo { player_id, msg } => {
+                if let Some(player) = world.players.iter().find(|p| p.id == player_id) {
+                    encode_and_send!(
+                        to_network,
+                        crate::network::PacketTypes::ServerSendMessage {
+                            player_name: "Server".to_string(),
+                            player_id: 0,
+                            msg,
+                        },
+                        player.addr
+                    );
+                }
+            }
+            PluginAction::SetBlock { x, y, block } => {
+                if let Err(e) = world
+                    .set_block_and_notify(to_web.clone(), to_network.clone(), x, y, block.into())
+                    .await
+                {
+                    c_error!(to_console, "plugin set_block({x}, {y}) failed: {e}");
+                }
+            }
+            PluginAction::Kick { player_id, msg } => {
+                world
+                    .kick(
+                        to_console.clone(),
+                        to_web.clone(),
+                        to_network.clone(),
+                        player_id,
+                        Some(&msg),
+                    )
+                    .await?;
+            }
+            PluginAction::Teleport { player_id, x, y } => {
+                if let Some(idx) = world.players.iter().position(|p| p.id == player_id) {
+                    let (old_x, old_y) = (
+                        world.players[idx].server_player.x,
+                        world.players[idx].server_player.y,
+                    );
+                    world.players[idx].server_player.x = x;
+                    world.players[idx].server_player.y = y;
+                    world.players[idx].server_player.velocity = Velocity::default();
+                    world.players[idx].server_player.acceleration = Acceleration::default();
+                    world.notify_player_moved(
+                        to_network.clone(),
+                        &world.players[idx].clone(),
+                        old_x,
+                        old_y,
+                    )?;
+                }
+            }
+            PluginAction::Respawn { player_id } => {
+                if let Some(idx) = world.players.iter().position(|p| p.id == player_id) {
+                    let spawn = world.get_spawn();
+                    let (old_x, old_y) = (
+                        world.players[idx].server_player.x,
+                        world.players[idx].server_player.y,
+                    );
+                    match Player::spawn_at(world, spawn) {
+                        Ok(new_player) => {
+                            world.players[idx].server_player = new_player;
+                            world.notify_player_moved(
+                                to_network.clone(),
+                                &world.players[idx].clone(),
+                                old_x,
+                                old_y,
+                            )?;
+                        }
+                        Err(e) => c_error!(to_console, "plugin respawn({player_id}) failed: {e}"),
+                    }
+                }
+            }
+            PluginAction::SetHealth { player_id, health } => {
+                if let Some(idx) = world.players.iter().position(|p| p.id == player_id) {
+                    world.players[idx].server_player.health = health.clamp(0.0, constants::MAX_HEALTH);
+                    encode_and_send!(
+                        to_network,
+                        crate::network::PacketTypes::ServerUpdateHealth {
+                            health: world.players[idx].server_player.health
+                        },
+                        world.players[idx].addr
+                    );
+                }
+            }
+            PluginAction::SetChannel { player_id, channel } => {
+                if let Some(player) = world.players.iter_mut().find(|p| p.id == player_id) {
+                    player.channel = channel;
+                }
+            }
+            PluginAction::GiveItem { player_id, item, count } => {
+                if let Some(idx) = world.players.iter().position(|p| p.id == player_id) {
+                    match world.players[idx]
+                        .server_player
+                        .insert(ItemStack { item, count, damage: 0 })
+                    {
+                        Ok(_) => world.players[idx]
+                            .server_player
+                            .notify_inventory_changed(to_network.clone(), world.players[idx].addr),
+                        Err(remaining) => c_error!(
+                            to_console,
+                            "plugin give_item: inventory full, {remaining} {item:?} could not fit"
+                        ),
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::console::ToConsole;
+use crate::{c_error, c_info};
+use std::future::Future;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+struct Task {
+    name: String,
+    handle: JoinHandle<()>,
+}
+
+/// Owns every long-running background task the server spawns - the console thread, the network
+/// thread, the admin dashboard, world generation, and any future job like autosaving or chunk
+/// meshing - plus a single `watch`-based shutdown signal those tasks can observe cooperatively.
+/// Replaces hand-tracking a fixed set of `JoinHandle`s with one subsystem whose [`shutdown`]
+/// waits for everything registered instead of a handful of hardcoded `.await.unwrap()` calls.
+///
+/// [`shutdown`]: TaskRunner::shutdown
+pub struct TaskRunner {
+    tasks: Vec<Task>,
+    shutdown_tx: watch::Sender<bool>,
+}
+
+impl TaskRunner {
+    pub fn new() -> Self {
+        let (shutdown_tx, _) = watch::channel(false);
+        TaskRunner {
+            tasks: Vec::new(),
+            shutdown_tx,
+        }
+    }
+
+    /// A receiver a background job can poll (via `changed()`/`borrow()`) to notice a shutdown was
+    /// requested without needing its own dedicated shutdown message.
+    pub fn shutdown_signal(&self) -> watch::Receiver<bool> {
+        self.shutdown_tx.subscribe()
+    }
+
+    /// Registers an already-spawned task under `name` so [`TaskRunner::shutdown`] waits for it.
+    pub fn register(&mut self, name: impl Into<String>, handle: JoinHandle<()>) {
+        self.tasks.push(Task {
+            name: name.into(),
+            handle,
+        });
+    }
+
+    /// Spawns `future` as a new task and registers it in one step. Kept for future jobs (autosave,
+    /// chunk meshing, ...) that don't need their own `tokio::spawn` call site.
+    #[allow(dead_code)]
+    pub fn spawn<F>(&mut self, name: impl Into<String>, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(future);
+        self.register(name, handle);
+    }
+
+    /// Broadcasts the shutdown signal, then awaits every registered task to completion, logging
+    /// (rather than panicking on) any task that panicked instead of returning normally.
+    pub async fn shutdown(self, to_console: ToConsole) {
+        let _ = self.shutdown_tx.send(true);
+        for task in self.tasks {
+            if let Err(e) = task.handle.await {
+                c_error!(to_console, "background task `{}` panicked: {e}", task.name);
+            }
+        }
+        c_info!(to_console, "All background tasks shut down.");
+    }
+}
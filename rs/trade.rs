@@ -0,0 +1,207 @@
+use crate::network::ToNetwork;
+use crate::player::ItemStack;
+use crate::world::{World, WorldError};
+use std::num::NonZeroU8;
+
+/// One side of a [`TradeSession`]: the inventory slots staged for trade and whether that side has
+/// confirmed. Staging a slot only snapshots it; the item stays in the offering player's inventory
+/// until the trade commits.
+#[derive(Debug, Clone, Default)]
+pub struct TradeOffer {
+    pub stakes: Vec<(u8, ItemStack)>,
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeSession {
+    pub from_id: u32,
+    pub to_id: u32,
+    pub from_offer: TradeOffer,
+    pub to_offer: TradeOffer,
+}
+
+impl TradeSession {
+    fn offer_for_mut(&mut self, player_id: u32) -> Option<&mut TradeOffer> {
+        if player_id == self.from_id {
+            Some(&mut self.from_offer)
+        } else if player_id == self.to_id {
+            Some(&mut self.to_offer)
+        } else {
+            None
+        }
+    }
+}
+
+fn player_exists(world: &World, id: u32) -> bool {
+    world.players.iter().any(|p| p.id == id)
+}
+
+fn find_trade_idx(world: &World, player_id: u32) -> Option<usize> {
+    world
+        .trades
+        .iter()
+        .position(|trade| trade.from_id == player_id || trade.to_id == player_id)
+}
+
+fn validate_stakes(world: &World, player_idx: usize, stakes: &[(u8, ItemStack)]) -> Result<(), WorldError> {
+    for &(slot, staged) in stakes {
+        match world.players[player_idx]
+            .server_player
+            .inventory
+            .get(slot as usize)
+        {
+            Some(Some(current))
+                if current.item == staged.item && current.count >= staged.count => {}
+            _ => return Err(WorldError::StaleTradeOffer(slot)),
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `recipient_idx` has room for every stake in `incoming`, simulating the inserts
+/// against a scratch clone of their inventory rather than the live one - so a full inventory is
+/// caught before [`confirm`] removes anything from the offering side, instead of after.
+fn validate_incoming_space(
+    world: &World,
+    recipient_idx: usize,
+    recipient_id: u32,
+    incoming: &[(u8, ItemStack)],
+) -> Result<(), WorldError> {
+    let mut scratch = world.players[recipient_idx].server_player.clone();
+    for &(_, staged) in incoming {
+        scratch
+            .insert(staged)
+            .map_err(|_| WorldError::TradeRecipientInventoryFull(recipient_id))?;
+    }
+    Ok(())
+}
+
+fn remove_stake(inventory: &mut [Option<ItemStack>; 9], slot: u8, staged: ItemStack) {
+    if let Some(current) = inventory[slot as usize] {
+        inventory[slot as usize] = NonZeroU8::new(current.count.get() - staged.count.get())
+            .map(|remaining| current.with_count(remaining));
+    }
+}
+
+/// Opens a trade session between `from_id` and `to_id`. Fails if either player doesn't exist or
+/// is already trading with someone else.
+pub fn open_trade(world: &mut World, from_id: u32, to_id: u32) -> Result<(), WorldError> {
+    if !player_exists(world, from_id) {
+        return Err(WorldError::NoSuchPlayer(from_id));
+    }
+    if !player_exists(world, to_id) {
+        return Err(WorldError::NoSuchPlayer(to_id));
+    }
+    if find_trade_idx(world, from_id).is_some() {
+        return Err(WorldError::AlreadyTrading(from_id));
+    }
+    if find_trade_idx(world, to_id).is_some() {
+        return Err(WorldError::AlreadyTrading(to_id));
+    }
+
+    world.trades.push(TradeSession {
+        from_id,
+        to_id,
+        from_offer: TradeOffer::default(),
+        to_offer: TradeOffer::default(),
+    });
+    Ok(())
+}
+
+/// Stages the stack currently in `slot` of `player_id`'s inventory into their side of the trade,
+/// un-confirming both sides so the other player has a chance to see the change before confirming
+/// again.
+pub fn offer_slot(world: &mut World, player_id: u32, slot: u8) -> Result<(), WorldError> {
+    let trade_idx = find_trade_idx(world, player_id).ok_or(WorldError::NoSuchTrade(player_id))?;
+    let player_idx = world
+        .players
+        .iter()
+        .position(|p| p.id == player_id)
+        .ok_or(WorldError::NoSuchPlayer(player_id))?;
+    let stack = world.players[player_idx]
+        .server_player
+        .inventory
+        .get(slot as usize)
+        .copied()
+        .flatten()
+        .ok_or(WorldError::StaleTradeOffer(slot))?;
+
+    let trade = &mut world.trades[trade_idx];
+    trade.from_offer.confirmed = false;
+    trade.to_offer.confirmed = false;
+    let offer = trade.offer_for_mut(player_id).unwrap_or_else(|| unreachable!());
+    offer.stakes.retain(|&(staged_slot, _)| staged_slot != slot);
+    offer.stakes.push((slot, stack));
+    Ok(())
+}
+
+/// Marks `player_id`'s side confirmed. If both sides are now confirmed, re-validates every staged
+/// stack against the live inventories (guarding against either player swapping a staged slot's
+/// contents after offering it) and checks each recipient actually has room for what's coming their
+/// way, then atomically swaps the staged stacks and notifies both clients. Both checks run before
+/// either side's stakes are removed, so a stale offer or a full inventory aborts the whole trade
+/// instead of destroying an item after the point of no return. Returns whether the trade committed.
+pub async fn confirm(
+    world: &mut World,
+    to_network: ToNetwork,
+    player_id: u32,
+) -> Result<bool, WorldError> {
+    let trade_idx = find_trade_idx(world, player_id).ok_or(WorldError::NoSuchTrade(player_id))?;
+    world.trades[trade_idx]
+        .offer_for_mut(player_id)
+        .unwrap_or_else(|| unreachable!())
+        .confirmed = true;
+
+    let trade = world.trades[trade_idx].clone();
+    if !(trade.from_offer.confirmed && trade.to_offer.confirmed) {
+        return Ok(false);
+    }
+
+    let from_idx = world
+        .players
+        .iter()
+        .position(|p| p.id == trade.from_id)
+        .ok_or(WorldError::NoSuchPlayer(trade.from_id))?;
+    let to_idx = world
+        .players
+        .iter()
+        .position(|p| p.id == trade.to_id)
+        .ok_or(WorldError::NoSuchPlayer(trade.to_id))?;
+
+    validate_stakes(world, from_idx, &trade.from_offer.stakes)?;
+    validate_stakes(world, to_idx, &trade.to_offer.stakes)?;
+    validate_incoming_space(world, to_idx, trade.to_id, &trade.from_offer.stakes)?;
+    validate_incoming_space(world, from_idx, trade.from_id, &trade.to_offer.stakes)?;
+
+    for &(slot, staged) in &trade.from_offer.stakes {
+        remove_stake(&mut world.players[from_idx].server_player.inventory, slot, staged);
+    }
+    for &(slot, staged) in &trade.to_offer.stakes {
+        remove_stake(&mut world.players[to_idx].server_player.inventory, slot, staged);
+    }
+    for &(_, staged) in &trade.from_offer.stakes {
+        let _ = world.players[to_idx].server_player.insert(staged);
+    }
+    for &(_, staged) in &trade.to_offer.stakes {
+        let _ = world.players[from_idx].server_player.insert(staged);
+    }
+
+    let (from_addr, to_addr) = (world.players[from_idx].addr, world.players[to_idx].addr);
+    world.players[from_idx]
+        .server_player
+        .notify_inventory_changed(to_network.clone(), from_addr);
+    world.players[to_idx]
+        .server_player
+        .notify_inventory_changed(to_network, to_addr);
+
+    world.trades.remove(trade_idx);
+    Ok(true)
+}
+
+/// Cancels the trade `player_id` is part of. Staged stacks never left the owning player's
+/// inventory, so canceling is just dropping the session.
+pub fn cancel(world: &mut World, player_id: u32) -> Result<(), WorldError> {
+    let trade_idx = find_trade_idx(world, player_id).ok_or(WorldError::NoSuchTrade(player_id))?;
+    world.trades.remove(trade_idx);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::encode_and_send;
+use crate::network::{PacketTypes, ToNetwork};
+use crate::world::World;
+use std::io;
+
+/// A game-state change produced while handling a packet, queued instead of being applied (and
+/// serialized) inline - see [`apply_updates`]. This is the start of migrating
+/// `process_client_packet`'s scattered `world.players[idx] = ...` mutations onto an
+/// inbox/outbox-style pipeline, one handler at a time; `ClientTryAttack` is the first, since it's
+/// also the branch that used to clone the whole attacked [`crate::network::ClientConnection`] just
+/// to work around the borrow checker. Expect more variants (a broadcast chat message, a player
+/// move) as more of the dispatcher migrates.
+#[derive(Debug, Clone)]
+pub enum Update {
+    /// Sets `player_id`'s health and notifies them of the new value.
+    HealthChanged { player_id: u32, health: f32 },
+    /// Adds an instantaneous acceleration impulse to `player_id`, e.g. combat knockback.
+    Knockback { player_id: u32, x: f32, y: f32 },
+}
+
+/// Applies every queued [`Update`] to `world`, in order, sending whatever packets each one implies.
+/// A player who disconnected between queuing and draining (e.g. a plugin hook kicked them) is
+/// silently skipped, same as the direct-mutation code this replaces already did via `idx` lookups.
+pub async fn apply_updates(to_network: ToNetwork, world: &mut World, updates: Vec<Update>) -> io::Result<()> {
+    for update in updates {
+        match update {
+            Update::HealthChanged { player_id, health } => {
+                if let Some(player) = world.players.iter_mut().find(|p| p.id == player_id) {
+                    player.server_player.health = health;
+                    encode_and_send!(to_network, PacketTypes::ServerUpdateHealth { health }, player.addr);
+                }
+            }
+            Update::Knockback { player_id, x, y } => {
+                if let Some(player) = world.players.iter_mut().find(|p| p.id == player_id) {
+                    player.server_player.acceleration.x = x;
+                    player.server_player.acceleration.y = y;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::console::{Command, Stats, ToConsole};
+use crate::world::Block;
+use crate::{c_error, c_info};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use thiserror::Error;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Error, Debug)]
+pub enum WebError {
+    #[error("error binding admin web port: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Everything the admin WebSocket dashboard can be told about: the same per-second [`Stats`] the
+/// TUI console already renders, plus block changes and player join/leave, so a remote operator
+/// doesn't have to attach to the TTY to watch the world live.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum WebEvent {
+    Stats(Stats),
+    BlockChange { x: u32, y: u32, block: Block },
+    PlayerJoin { id: u32, name: String },
+    PlayerLeave { id: u32 },
+}
+
+pub type ToWeb = broadcast::Sender<WebEvent>;
+
+/// Creates the broadcast channel [`WebEvent`]s are published on. Safe to create and send on even
+/// if `--web-port` is never set - broadcasting with no subscribers is a no-op.
+pub fn channel() -> ToWeb {
+    broadcast::channel(64).0
+}
+
+/// Starts the admin WebSocket server on `bind_addr`. Every connected browser receives every
+/// [`WebEvent`] sent on `to_web` as a JSON text frame, and any text frame it sends back is parsed
+/// as a console [`Command`] and routed into `to_main`, exactly as if it had been typed into the
+/// TUI console. The accept loop stops as soon as `shutdown` fires, so the returned task can be
+/// registered with a [`crate::tasks::TaskRunner`] and awaited to completion.
+pub async fn init(
+    bind_addr: SocketAddr,
+    to_console: ToConsole,
+    to_web: ToWeb,
+    to_main: UnboundedSender<Command>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<JoinHandle<()>, WebError> {
+    let listener = TcpListener::bind(bind_addr).await?;
+    c_info!(to_console, "Admin dashboard listening on {bind_addr}");
+    Ok(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => {
+                    c_info!(to_console, "Admin dashboard shutting down");
+                    break;
+                }
+                accepted = listener.accept() => {
+                    let (stream, addr) = match accepted {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            c_error!(to_console, "error accepting admin dashboard connection: {e}");
+                            continue;
+                        }
+                    };
+                    tokio::spawn(handle_connection(
+                        stream,
+                        addr,
+                        to_console.clone(),
+                        to_web.subscribe(),
+                        to_main.clone(),
+                    ));
+                }
+            }
+        }
+    }))
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    to_console: ToConsole,
+    mut from_web: broadcast::Receiver<WebEvent>,
+    to_main: UnboundedSender<Command>,
+) {
+    let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws_stream) => ws_stream,
+        Err(e) => {
+            c_error!(to_console, "admin dashboard handshake with {addr} failed: {e}");
+            return;
+        }
+    };
+    c_info!(to_console, "admin dashboard connected from {addr}");
+    let (mut write, mut read) = ws_stream.split();
+    loop {
+        tokio::select! {
+            event = from_web.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        c_error!(to_console, "admin dashboard {addr} lagged, dropped {skipped} events");
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Ok(json) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if write.send(Message::Text(json)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = read.next() => {
+                match incoming {
+                    Some(Ok(Message::Text(text))) => match Command::from_str(&text) {
+                        Ok(command) => {
+                            let _ = to_main.send(command);
+                        }
+                        Err(e) => c_error!(to_console, "admin dashboard {addr} sent invalid command: {e}"),
+                    },
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        c_error!(to_console, "admin dashboard {addr} connection error: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    c_info!(to_console, "admin dashboard {addr} disconnected");
+}
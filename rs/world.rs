@@ -1,6 +1,9 @@
+use crate::backups::BackupInfo;
+use crate::trade::TradeSession;
 use crate::console::ToConsole;
 use crate::network::{ClientConnection, PacketTypes, ToNetwork};
-use crate::player::{Item, Player, Surrounding};
+use crate::player::{DetachedInventory, Item, ItemStack, Player, Surrounding};
+use crate::web::{ToWeb, WebEvent};
 use crate::{c_debug, c_error, c_info, WorldType};
 use fast_poisson::Poisson;
 use itertools::Itertools;
@@ -9,14 +12,13 @@ use rand::rngs::SmallRng;
 use rand::{Rng, RngCore, SeedableRng};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io;
 use std::iter::zip;
 use std::net::SocketAddr;
-use std::num::NonZeroU32;
-use std::ops::Range;
+use std::num::{NonZeroU32, NonZeroU8};
 use std::time::{Duration, Instant};
-use strum::EnumString;
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -39,6 +41,18 @@ pub enum WorldError {
     SpawnRangeTooLarge,
     #[error("error propagating changes to clients: {0}")]
     NetworkError(#[from] io::Error),
+    #[error("no detached inventory named `{0}`")]
+    NoSuchDetachedInventory(String),
+    #[error("player {0} does not exist")]
+    NoSuchPlayer(u32),
+    #[error("player {0} is already in a trade")]
+    AlreadyTrading(u32),
+    #[error("no active trade involving player {0}")]
+    NoSuchTrade(u32),
+    #[error("slot {0} is empty or no longer holds what was staged")]
+    StaleTradeOffer(u8),
+    #[error("player {0}'s inventory has no room for the other side's stakes")]
+    TradeRecipientInventoryFull(u32),
 }
 
 #[derive(Debug)]
@@ -59,9 +73,76 @@ pub struct World {
     pub players: Vec<ClientConnection>,
     player_loaded: Vec<Vec<u32>>,
     pub physics_update_queue: HashMap<u32, PositionUpdate>,
+    /// Block changes pending a batched `ServerBatchUpdateBlock` notification, flushed every
+    /// `packet_update_tick` instead of sending one packet per block.
+    block_update_queue: Vec<(u32, u32, Block)>,
+    /// Cells due a scheduled tick, tagged with the block that queued them so
+    /// [`World::run_scheduled_tick`]/[`World::run_scheduled_tick_and_notify`] know which
+    /// behavior to run. Drained every `world_tick` by [`World::tick_scheduled_blocks`] (and, at
+    /// world-generation time, by [`World::init_scheduled_ticks`]). Only `Block::Water` queues
+    /// anything here today.
     to_update: HashSet<(u32, u32, Block)>,
     pub spawn_point: u32,
     pub spawn_range: NonZeroU32,
+    /// Backups taken with `Command::Backup`, newest last. Kept in memory so `Command::ListBackups`
+    /// doesn't need to rescan the backups directory.
+    pub backups: Vec<BackupInfo>,
+    /// Named inventories not tied to any single connection, keyed by name. See
+    /// [`DetachedInventory`].
+    pub detached_inventories: HashMap<String, DetachedInventory>,
+    /// Trades in progress, at most one per player. See [`TradeSession`].
+    pub trades: Vec<TradeSession>,
+    /// The seed terrain generation used, if this world was generated with `WorldType::Terrain`.
+    /// Saved in the world file header purely as provenance; reloading a world never regenerates
+    /// it from this.
+    pub generation_seed: Option<u64>,
+    /// Structure blocks (currently just tree canopies) waiting to be applied via
+    /// [`World::flush_placement_queue`] instead of being written immediately - so a tree whose
+    /// canopy reaches into a column terrain generation hasn't reached yet isn't immediately
+    /// overwritten once that column's own blocks get set.
+    placement_queue: Vec<BlockPos>,
+    /// The fill level (`1..=WATER_MAX_LEVEL`) of each [`Block::Water`] placed through
+    /// [`World::set_water_level`]. A `Block::Water` with no entry here is a full source (a lake
+    /// or ocean stamped down directly by terrain generation, or loaded from a save predating this
+    /// map) and behaves as though its entry were `WATER_MAX_LEVEL`. There's nowhere to carry this
+    /// on `Block` itself - its variants are plain, explicitly-discriminanted `u8` ids for the wire
+    /// protocol - so it's tracked out-of-band instead, the same way `Chunk`'s palette doesn't
+    /// carry per-position metadata either.
+    water_levels: HashMap<(u32, u32), u8>,
+    /// Packed `(skylight << 4) | block_light` per lit cell, each channel `0..=LIGHT_MAX`. Unlike
+    /// [`World::water_levels`], absence here means "not yet computed" (both channels `0`) rather
+    /// than "full" - most of a world is unlit by either channel, so `0` needs to be the cheap,
+    /// implicit case. Neither channel is part of the on-disk world file - see
+    /// [`World::init_light`], which reseeds both from the block grid after generation or load.
+    light_levels: HashMap<(u32, u32), u8>,
+    /// Cells awaiting a light flood-fill step, queued by [`World::relight_cell`] whenever a block
+    /// change affects what a cell occludes or emits. Drained in FIFO order by
+    /// [`World::propagate_light`] - unlike `to_update`, which is only visited once per tick, this
+    /// runs to completion (enqueueing more of itself as light spreads) every time it's touched.
+    light_queue: VecDeque<LightUpdate>,
+}
+
+/// The highest fill level a [`Block::Water`] cell can hold before it's considered a full source.
+const WATER_MAX_LEVEL: u8 = 8;
+
+/// The brightest either light channel can be.
+const LIGHT_MAX: u8 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LightChannel {
+    Sky,
+    Block,
+}
+
+/// A single step of [`World::propagate_light`]'s flood-fill, queued in [`World::light_queue`].
+#[derive(Debug)]
+enum LightUpdate {
+    /// `(x, y)`'s `LightChannel` level just rose (a fresh source, or an occluder cleared) -
+    /// spread it to neighbours via [`World::spread_light`].
+    Increase(u32, u32, LightChannel),
+    /// `(x, y)`'s `LightChannel` level just fell from `old_level` (a source was removed or
+    /// blocked) - darken whatever [`World::unspread_light`] finds was only lit by it.
+    Decrease(u32, u32, LightChannel, u8),
 }
 
 struct SurroundingBlocks {
@@ -71,12 +152,68 @@ struct SurroundingBlocks {
     right: Option<BlockPos>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chunk {
     pub size: u32,
     pub chunk_x: u32,
     pub chunk_y: u32,
-    pub blocks: Vec<Block>,
+    storage: ChunkStorage,
+}
+
+/// A paletted, bit-packed block container. Most chunks (especially all-air ones, which dominate
+/// in a tall world) only ever hold a handful of distinct block types, so instead of one full
+/// `Block` byte per cell we keep a small `palette` of the blocks actually present plus one packed
+/// index per cell, sized to just fit `palette.len()` - collapsing to [`ChunkStorage::Uniform`],
+/// with no index storage at all, while every cell is still the same block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ChunkStorage {
+    /// Every cell in the chunk is `block`.
+    Uniform(Block),
+    Paletted {
+        palette: Vec<Block>,
+        bits_per_entry: u32,
+        /// `cell_count * bits_per_entry` bits, packed low-bit-first, `ceil(.. / 8)` bytes long.
+        indices: Vec<u8>,
+    },
+}
+
+/// The number of bits needed to index `palette_len` distinct values (`0` once `palette_len <= 1`,
+/// since [`ChunkStorage::Uniform`] is used instead and no index storage exists at all).
+fn bits_needed(palette_len: usize) -> u32 {
+    if palette_len <= 1 {
+        0
+    } else {
+        usize::BITS - (palette_len - 1).leading_zeros()
+    }
+}
+
+fn packed_byte_len(cell_count: usize, bits_per_entry: u32) -> usize {
+    (cell_count * bits_per_entry as usize).div_ceil(8)
+}
+
+fn get_packed_index(data: &[u8], bits_per_entry: u32, cell: usize) -> u32 {
+    let bit_start = cell * bits_per_entry as usize;
+    let mut value = 0u32;
+    for bit in 0..bits_per_entry as usize {
+        let pos = bit_start + bit;
+        if data[pos / 8] & (1 << (pos % 8)) != 0 {
+            value |= 1 << bit;
+        }
+    }
+    value
+}
+
+fn set_packed_index(data: &mut [u8], bits_per_entry: u32, cell: usize, value: u32) {
+    let bit_start = cell * bits_per_entry as usize;
+    for bit in 0..bits_per_entry as usize {
+        let pos = bit_start + bit;
+        let byte = &mut data[pos / 8];
+        if value & (1 << bit) != 0 {
+            *byte |= 1 << (pos % 8);
+        } else {
+            *byte &= !(1 << (pos % 8));
+        }
+    }
 }
 
 pub type BlockPos = (u32, u32, Block);
@@ -85,11 +222,15 @@ pub struct BlockProperties {
     solid: bool,
     item: Option<Item>,
     hardness: u8,
+    /// How brightly this block lights up its own cell, `0..=LIGHT_MAX`. `0` for every block
+    /// today - no emitter exists yet - but [`World::seed_block_light`] is wired up to pick up the
+    /// first one that sets this above `0`.
+    light_emission: u8,
 }
 
 macro_rules! define_blocks {
     ($($name: ident = $id: expr => { $($prop_name:ident : $prop_value:expr),* $(,)? }),* $(,)?) => {
-        #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, EnumString)]
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Hash, EnumString, EnumIter)]
         pub enum Block {
             $($name = $id),*
         }
@@ -119,6 +260,14 @@ macro_rules! define_blocks {
             block.properties().solid
         }
 
+        pub fn hardness(block: Block) -> u8 {
+            block.properties().hardness
+        }
+
+        pub fn light_emission(block: Block) -> u8 {
+            block.properties().light_emission
+        }
+
         impl From<Block> for Option<Item> {
             fn from(block: Block) -> Self {
                 block.properties().item
@@ -137,43 +286,182 @@ struct TerrainSettings {
     redistribution_factor: f64,
     cave_gen_size: f64,
     tree_spawn_radius: f64,
+    biome_scale: f64,
+}
+
+/// A world column's biome, selected by a low-frequency Perlin band in [`World::generate_terrain`]
+/// that's entirely independent of the height noise - so a desert can sit right next to a forest
+/// without the terrain shape itself caring.
+#[derive(Debug, Copy, Clone, EnumIter)]
+enum Biome {
+    Plains,
+    Desert,
+    Mountains,
+    Forest,
+}
+
+/// The blocks and tree behavior a [`Biome`] fills its columns with.
+struct BiomeProperties {
+    /// The block placed at the surface, instead of the usual `Grass`.
+    surface: Block,
+    /// The block filling the `subsurface_depth` blocks below the surface, before the column falls
+    /// back to `Stone`.
+    filler: Block,
+    subsurface_depth: u32,
+    /// The tree species grown in this biome, or `None` if it grows no trees at all (e.g. `Desert`).
+    tree_type: Option<TreeTypes>,
+    /// Multiplies the base `tree_spawn_radius` for this biome's share of the Poisson tree
+    /// candidates - below 1.0 packs trees closer together (denser), above 1.0 spreads them out.
+    tree_density_mul: f64,
+}
+
+impl Biome {
+    fn properties(self) -> BiomeProperties {
+        match self {
+            Biome::Plains => BiomeProperties {
+                surface: Block::Grass,
+                filler: Block::Dirt,
+                subsurface_depth: 3,
+                tree_type: Some(TreeTypes::Basic),
+                tree_density_mul: 1.0,
+            },
+            Biome::Desert => BiomeProperties {
+                surface: Block::Sand,
+                filler: Block::Sand,
+                subsurface_depth: 5,
+                tree_type: None,
+                tree_density_mul: 1.0,
+            },
+            Biome::Mountains => BiomeProperties {
+                surface: Block::Stone,
+                filler: Block::Stone,
+                subsurface_depth: 0,
+                tree_type: None,
+                tree_density_mul: 1.0,
+            },
+            Biome::Forest => BiomeProperties {
+                surface: Block::Grass,
+                filler: Block::Dirt,
+                subsurface_depth: 4,
+                tree_type: Some(TreeTypes::Basic),
+                tree_density_mul: 0.5,
+            },
+        }
+    }
+
+    /// Quantizes `selector` (a Perlin sample already normalized to `0.0..1.0`) into as many
+    /// equal-width bands as there are [`Biome`] variants, in declaration order.
+    fn from_selector(selector: f64) -> Biome {
+        let variants: Vec<Biome> = Biome::iter().collect();
+        let band = ((selector * variants.len() as f64) as usize).min(variants.len() - 1);
+        variants[band]
+    }
+}
+
+// Distinct, arbitrary high `purpose` values for `derive_seed`'s noise passes below - well clear of
+// the small pass indices (`0..noise_passes`) so neither can collide with the other.
+const SEED_PURPOSE_CAVE: u64 = u64::MAX - 1;
+const SEED_PURPOSE_BIOME: u64 = u64::MAX - 2;
+const SEED_PURPOSE_TREE_POISSON: u64 = u64::MAX - 3;
+const SEED_PURPOSE_TREE_THINNING: u64 = u64::MAX - 4;
+const SEED_PURPOSE_TREE_HEIGHT: u64 = u64::MAX - 5;
+
+/// Trunk heights (in `Wood` blocks) a generated tree can roll, inclusive.
+const TREE_MIN_HEIGHT: u32 = 4;
+const TREE_MAX_HEIGHT: u32 = 7;
+
+/// Deterministically mixes the world's `master_seed` with a `purpose` (one of the `SEED_PURPOSE_*`
+/// constants, or a height noise pass index) into an independent sub-seed, splitmix64-style. Using
+/// this instead of pulling seeds off a single sequential RNG means every noise pass's seed is a
+/// pure function of `(master_seed, purpose)` alone - regenerating one column's terrain never
+/// requires first replaying whichever passes came before it.
+fn derive_seed(master_seed: u64, purpose: u64) -> u64 {
+    let mut z = master_seed ^ purpose.wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
 }
 
+/// The terrain height at column `x`: a pure function of `x`, `terrain_settings` and `master_seed`,
+/// reconstructing each height noise pass's `Perlin` generator from its derived seed rather than
+/// depending on state carried over from neighboring columns.
+fn column_height(x: u32, terrain_settings: &TerrainSettings, master_seed: u64, height_range: f64) -> u32 {
+    let x_f = x as f64 * 0.005;
+    let mut multiplier = 0.0;
+    let mut octaves = 0.0;
+    for pass in 0..terrain_settings.noise_passes {
+        let perlin = Perlin::new(derive_seed(master_seed, pass as u64) as u32);
+        let pass_2n = 2f64.powi(pass as i32);
+        let noise = perlin.get([x_f * pass_2n]) / 2.0 + 0.5;
+        let octave = 1f64 / pass_2n;
+        multiplier += octave * noise;
+        octaves += octave;
+    }
+    multiplier /= octaves;
+    multiplier = multiplier.powf(terrain_settings.redistribution_factor);
+    terrain_settings.base_height + (multiplier * height_range).round() as u32
+}
+
+/// Cave carving noise at block `(x, y)`: a pure function of its position and `master_seed`.
+fn cave_noise(x: f64, y: f64, master_seed: u64) -> f64 {
+    let simplex = OpenSimplex::new(derive_seed(master_seed, SEED_PURPOSE_CAVE) as u32);
+    simplex.get([x * 0.001 * 32.0, y * 0.001 * 32.0]).abs()
+}
+
+/// The [`Biome`] at column `x`: a pure function of its position, `biome_scale` and `master_seed`.
+/// A single low-frequency Perlin pass, much wider than any of `column_height`'s, kept completely
+/// independent of the height noise so biomes and terrain shape vary on their own axes.
+fn biome_for_column(x: u32, biome_scale: f64, master_seed: u64) -> Biome {
+    let perlin = Perlin::new(derive_seed(master_seed, SEED_PURPOSE_BIOME) as u32);
+    let selector = perlin.get([x as f64 * biome_scale]) / 2.0 + 0.5;
+    Biome::from_selector(selector)
+}
+
+#[derive(Debug, Copy, Clone)]
 enum TreeTypes {
     Basic,
 }
+// Drops any cell whose offset would land at a negative x or y instead of letting it wrap into a
+// huge u32 (e.g. a tree planted at x = 0 has no column to its left for a `-1` leaf offset to land
+// on) - callers still need to check the *upper* world bounds themselves, since a trunk position
+// alone doesn't know how wide or tall the world is.
 macro_rules! map_to_trunk {
     ($trunk_x: expr, $trunk_y: expr, $trunk_offset: expr, $spaces: expr) => {
         $spaces
             .into_iter()
-            .map(|(x, y, block)| {
-                (
-                    (x + $trunk_x as i32) as u32,
-                    (y + $trunk_y as i32) as u32,
-                    block,
-                )
+            .filter_map(|(x, y, block)| {
+                let abs_x = x + $trunk_x as i32;
+                let abs_y = y + $trunk_y as i32;
+                (abs_x >= 0 && abs_y >= 0).then_some((abs_x as u32, abs_y as u32, block))
             })
             .collect()
     };
 }
 impl TreeTypes {
-    pub fn get_required_blocks(tree: TreeTypes, trunk_x: u32, trunk_y: u32) -> Vec<BlockPos> {
+    /// `trunk_height` is how many `Wood` blocks tall the trunk is (`TREE_MIN_HEIGHT..=TREE_MAX_HEIGHT`)
+    /// - the canopy always sits the same three layers above the trunk's top block regardless of
+    /// how tall that makes it.
+    pub fn get_required_blocks(
+        tree: TreeTypes,
+        trunk_x: u32,
+        trunk_y: u32,
+        trunk_height: u32,
+    ) -> Vec<BlockPos> {
         match tree {
             TreeTypes::Basic => {
-                let layout = vec![
-                    (0, 5, Block::Leaves),
-                    (-1, 4, Block::Leaves),
-                    (0, 4, Block::Leaves),
-                    (1, 4, Block::Leaves),
-                    (-2, 3, Block::Leaves),
-                    (-1, 3, Block::Leaves),
-                    (0, 3, Block::Wood),
-                    (1, 3, Block::Leaves),
-                    (2, 3, Block::Leaves),
-                    (0, 2, Block::Wood),
-                    (0, 1, Block::Wood),
-                    (0, 0, Block::Wood),
-                ];
+                let top = trunk_height as i32 - 1;
+                let mut layout: Vec<(i32, i32, Block)> =
+                    (0..trunk_height as i32).map(|y| (0, y, Block::Wood)).collect();
+                layout.extend([
+                    (-2, top, Block::Leaves),
+                    (-1, top, Block::Leaves),
+                    (1, top, Block::Leaves),
+                    (2, top, Block::Leaves),
+                    (-1, top + 1, Block::Leaves),
+                    (0, top + 1, Block::Leaves),
+                    (1, top + 1, Block::Leaves),
+                    (0, top + 2, Block::Leaves),
+                ]);
                 map_to_trunk!(trunk_x, trunk_y, 2, layout)
             }
         }
@@ -199,7 +487,7 @@ impl World {
             spawn_range,
         )?;
 
-        Ok(match type_settings {
+        let mut world = match type_settings {
             WorldType::Empty => base,
             WorldType::Flat { grass_height } => {
                 World::generate_flat(to_console, base, grass_height)?
@@ -213,6 +501,7 @@ impl World {
                 water_height,
                 cave_gen_size,
                 tree_spawn_radius,
+                biome_scale,
             } => World::generate_terrain(
                 to_console,
                 base,
@@ -225,9 +514,12 @@ impl World {
                     redistribution_factor,
                     cave_gen_size,
                     tree_spawn_radius,
+                    biome_scale,
                 },
             )?,
-        })
+        };
+        world.init_light()?;
+        Ok(world)
     }
     fn generate_empty(
         to_console: ToConsole,
@@ -269,12 +561,71 @@ impl World {
                 player_loaded,
                 to_update: HashSet::new(),
                 physics_update_queue: HashMap::new(),
+                block_update_queue: Vec::new(),
                 spawn_point,
                 spawn_range,
+                backups: Vec::new(),
+                detached_inventories: HashMap::new(),
+                trades: Vec::new(),
+                generation_seed: None,
+                placement_queue: Vec::new(),
+                water_levels: HashMap::new(),
+                light_levels: HashMap::new(),
+                light_queue: VecDeque::new(),
             })
         }
     }
 
+    /// Rebuilds a [`World`] from the header fields and chunk grid read back by
+    /// [`crate::worldfile::load`], the same way [`World::generate_empty`] builds one from scratch
+    /// - runtime-only state (players, trades, pending updates) always starts empty, since none of
+    /// that is part of a world file.
+    pub fn from_save(
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+        spawn_point: u32,
+        spawn_range: NonZeroU32,
+        generation_seed: Option<u64>,
+        chunks: Vec<Chunk>,
+    ) -> Result<World, WorldError> {
+        if width % chunk_size != 0 || height % chunk_size != 0 {
+            return Err(WorldError::MismatchedChunkSize);
+        }
+        let width_chunks = width / chunk_size;
+        let height_chunks = height / chunk_size;
+        if chunks.len() as u32 != width_chunks * height_chunks {
+            return Err(WorldError::MismatchedChunkSize);
+        }
+        let player_loaded = vec![vec![]; chunks.len()];
+
+        let mut world = World {
+            width,
+            height,
+            chunk_size,
+            chunks,
+            width_chunks,
+            height_chunks,
+            players: vec![],
+            player_loaded,
+            to_update: HashSet::new(),
+            physics_update_queue: HashMap::new(),
+            block_update_queue: Vec::new(),
+            spawn_point,
+            spawn_range,
+            backups: Vec::new(),
+            detached_inventories: HashMap::new(),
+            trades: Vec::new(),
+            generation_seed,
+            placement_queue: Vec::new(),
+            water_levels: HashMap::new(),
+            light_levels: HashMap::new(),
+            light_queue: VecDeque::new(),
+        };
+        world.init_light()?;
+        Ok(world)
+    }
+
     fn generate_flat(
         to_console: ToConsole,
         mut world: World,
@@ -303,13 +654,19 @@ impl World {
         Ok(world)
     }
 
+    /// Builds natural-looking terrain from layered Perlin noise: [`column_height`] combines a
+    /// low-frequency "hilliness" pass with several higher-frequency detail passes into each
+    /// column's surface height, [`cave_noise`] carves air pockets out of the stone below it, and
+    /// [`biome_for_column`] (its own, even-lower-frequency pass) picks what the surface and filler
+    /// blocks actually are. Everything below `water_height` that's still air after carving floods
+    /// with `Block::Water`, and trees are scattered across suitable surface columns via a
+    /// Poisson-disk pass. Every noise function is reconstructed from `terrain_settings.seed` (or a
+    /// freshly rolled one) through [`derive_seed`], so the whole world is fully determined by it.
     fn generate_terrain(
         to_console: ToConsole,
         mut world: World,
         terrain_settings: TerrainSettings,
     ) -> Result<World, WorldError> {
-        type TerrainGenerator = Box<dyn FnMut(f64, f64, f64) -> (f64, f64)>;
-
         let start = Instant::now();
 
         if terrain_settings.upper_height > world.height {
@@ -324,31 +681,24 @@ impl World {
         let cave_gen_size = terrain_settings.cave_gen_size.clamp(0.0, 1.0);
 
         let master_seed = terrain_settings.seed.unwrap_or(rand::rng().next_u64());
-        let mut seed_generator = SmallRng::seed_from_u64(master_seed);
+        world.generation_seed = Some(master_seed);
         let height_range = (terrain_settings.upper_height - terrain_settings.base_height) as f64;
 
-        let mut generators: Vec<TerrainGenerator> = (0..terrain_settings.noise_passes)
-            .map(|pass| {
-                let seed = seed_generator.next_u32();
-                Box::new(move |x_f, multiplier, octaves| {
-                    let perlin = Perlin::new(seed);
-                    let pass_2n = 2f64.powi(pass as i32);
-                    let noise = perlin.get([x_f * pass_2n]) / 2.0 + 0.5;
-                    let octave = 1f64 / pass_2n;
-                    (multiplier + (octave * noise), octaves + octave)
-                }) as TerrainGenerator
-            })
-            .collect();
-        let cave_generator = {
-            let seed = seed_generator.next_u32();
-            move |x, y| {
-                let simplex = OpenSimplex::new(seed);
-                simplex.get([x * 0.001 * 32.0, y * 0.001 * 32.0]).abs()
-            }
-        };
+        // `fast_poisson` only supports a single radius for the whole dimension, but each biome
+        // wants its own tree density. We generate candidates at the densest biome's radius, then
+        // thin each candidate down in the main loop below based on how much sparser its own
+        // biome's `tree_density_mul` wants it to be.
+        let min_tree_density_mul = Biome::iter()
+            .map(|biome| biome.properties().tree_density_mul)
+            .fold(f64::INFINITY, f64::min);
+        let densest_tree_radius = terrain_settings.tree_spawn_radius * min_tree_density_mul;
+        let mut tree_thinning =
+            SmallRng::seed_from_u64(derive_seed(master_seed, SEED_PURPOSE_TREE_THINNING));
+        let mut tree_height =
+            SmallRng::seed_from_u64(derive_seed(master_seed, SEED_PURPOSE_TREE_HEIGHT));
         let mut trees = Poisson::<1>::new()
-            .with_seed(seed_generator.next_u64())
-            .with_dimensions([world.width as f64], terrain_settings.tree_spawn_radius)
+            .with_seed(derive_seed(master_seed, SEED_PURPOSE_TREE_POISSON))
+            .with_dimensions([world.width as f64], densest_tree_radius)
             .into_iter()
             .map(|pos| pos[0].round() as u32)
             .unique()
@@ -358,20 +708,12 @@ impl World {
 
         let mut next_tree = trees.next();
         for x in 0..world.width {
-            let x_f = x as f64 * 0.005;
-            let mut multiplier = 0.0;
-            let mut octaves = 0.0;
-            generators.iter_mut().for_each(|generator| {
-                (multiplier, octaves) = generator(x_f, multiplier, octaves);
-            });
-            multiplier /= octaves;
-            multiplier = multiplier.powf(terrain_settings.redistribution_factor);
-            let height = terrain_settings.base_height + (multiplier * height_range).round() as u32;
+            let height = column_height(x, &terrain_settings, master_seed, height_range);
 
             let (mut top_y, mut prev_top_y) = (0u32, 0u32);
             for y in 0..=u32::max(height, terrain_settings.water_height) {
                 let block = {
-                    let noise_here = cave_generator(x as f64, y as f64);
+                    let noise_here = cave_noise(x as f64, y as f64, master_seed);
                     if noise_here < cave_gen_size {
                         Block::Air
                     } else {
@@ -387,25 +729,48 @@ impl World {
                 }
             }
 
-            let should_place_grass = top_y > terrain_settings.water_height;
+            let biome = biome_for_column(x, terrain_settings.biome_scale, master_seed);
+            let biome_properties = biome.properties();
+            let should_place_surface = top_y > terrain_settings.water_height;
             if top_y - prev_top_y != 1 {
                 if !is_solid(world.get_block(x, top_y)?) {
                     world.set_block(x, top_y, Block::Air)?;
                 }
-            } else if should_place_grass {
-                world.set_block(x, top_y, Block::Grass)?;
+            } else if should_place_surface {
+                world.set_block(x, top_y, biome_properties.surface)?;
+                for depth in 1..=biome_properties.subsurface_depth {
+                    if depth > top_y {
+                        break;
+                    }
+                    let filler_y = top_y - depth;
+                    if is_solid(world.get_block(x, filler_y)?) {
+                        world.set_block(x, filler_y, biome_properties.filler)?;
+                    }
+                }
             }
 
             if let Some(tree) = next_tree {
                 if x == tree {
-                    if should_place_grass {
-                        let _ = world.generate_tree_at(x, top_y + 1);
+                    if should_place_surface {
+                        if let Some(tree_type) = biome_properties.tree_type {
+                            let keep_probability =
+                                (min_tree_density_mul / biome_properties.tree_density_mul).min(1.0);
+                            if keep_probability >= 1.0
+                                || tree_thinning.random::<f64>() < keep_probability
+                            {
+                                let trunk_height =
+                                    tree_height.random_range(TREE_MIN_HEIGHT..=TREE_MAX_HEIGHT);
+                                world.generate_tree_at(x, top_y + 1, tree_type, trunk_height);
+                            }
+                        }
                     }
                     next_tree = trees.next();
                 }
             }
         }
 
+        world.flush_placement_queue()?;
+
         c_info!(
             to_console,
             "Generation of terrain with seed {} took {:?}.",
@@ -415,19 +780,38 @@ impl World {
 
         let start = Instant::now();
         while !world.to_update.is_empty() {
-            world.init_flow_water()?;
+            world.init_scheduled_ticks()?;
         }
         c_info!(to_console, "Flowing water took {:?}.", start.elapsed());
 
         Ok(world)
     }
 
-    fn generate_tree_at(&mut self, trunk_x: u32, trunk_y: u32) -> Result<(), WorldError> {
-        let space = TreeTypes::get_required_blocks(TreeTypes::Basic, trunk_x, trunk_y);
-        space.into_iter().try_for_each(|(x, y, block)| {
-            self.raw_set_block(x, y, block)?;
-            Ok(())
-        })
+    /// Queues a tree's blocks for [`World::flush_placement_queue`] rather than writing them
+    /// immediately - during terrain generation the canopy can reach into a column that hasn't
+    /// been generated yet, and an immediate write there would just get overwritten once that
+    /// column's own terrain pass runs.
+    fn generate_tree_at(&mut self, trunk_x: u32, trunk_y: u32, tree_type: TreeTypes, trunk_height: u32) {
+        let space = TreeTypes::get_required_blocks(tree_type, trunk_x, trunk_y, trunk_height);
+        self.placement_queue.extend(space);
+    }
+
+    /// Applies every block in the placement queue that's currently in world bounds, via
+    /// [`World::set_block`] (so a placed trunk/canopy gets the same `water_levels`/light
+    /// bookkeeping any other block change does), and leaves whatever's still out of bounds queued
+    /// for a later flush.
+    pub(crate) fn flush_placement_queue(&mut self) -> Result<(), WorldError> {
+        let pending = std::mem::take(&mut self.placement_queue);
+        for (x, y, block) in pending {
+            match self.set_block(x, y, block) {
+                Ok(()) => {}
+                Err(WorldError::OutOfBoundsBlock(..) | WorldError::OutOfBoundsChunk(..)) => {
+                    self.placement_queue.push((x, y, block));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
     }
 
     fn check_out_of_bounds_chunk(&self, chunk_x: u32, chunk_y: u32) -> Result<(), WorldError> {
@@ -445,15 +829,45 @@ impl World {
         }
     }
 
+    /// Picks a spawn x-coordinate for the next joining player, spacing players already in the
+    /// world evenly around the spawn ring rather than clustering them at random.
     pub fn get_spawn(&self) -> u32 {
-        let spawn_range = Range {
-            start: self.spawn_point.saturating_sub(self.spawn_range.into()),
-            end: std::cmp::min(
-                self.spawn_point.saturating_add(self.spawn_range.into()),
-                self.width,
-            ),
-        };
-        rand::rng().random_range(spawn_range)
+        self.get_ring_spawn(self.players.len())
+    }
+
+    /// Computes the spawn x-coordinate for the `index`-th player (0-based, by join order) to
+    /// share the spawn ring, the interval `[spawn_point - spawn_range, spawn_point + spawn_range]`.
+    /// Candidate tiles are sorted by x and spaced `ring_len / (index + 1)` apart so players don't
+    /// telefrag or camp on top of each other. If the ideal tile's surface is blocked, the next
+    /// tile around the ring is tried instead; if the ring has no walkable tile at all, or the
+    /// range is effectively zero, this falls back to `spawn_point`.
+    fn get_ring_spawn(&self, index: usize) -> u32 {
+        let range = self.spawn_range.get();
+        let ring_start = self.spawn_point.saturating_sub(range);
+        let ring_end = std::cmp::min(
+            self.spawn_point.saturating_add(range),
+            self.width.saturating_sub(1),
+        );
+        if range == 0 || ring_end <= ring_start {
+            return self.spawn_point;
+        }
+        let ring_len = ring_end - ring_start;
+
+        let slot_count = index as u32 + 1;
+        let step = ring_len as f64 / slot_count as f64;
+        let offset = (index as f64 * step).round() as u32 % (ring_len + 1);
+
+        (0..=ring_len)
+            .map(|delta| ring_start + (offset + delta) % (ring_len + 1))
+            .find(|&x| self.is_spawn_tile_walkable(x))
+            .unwrap_or(self.spawn_point)
+    }
+
+    fn is_spawn_tile_walkable(&self, x: u32) -> bool {
+        self.get_highest_block_at(x)
+            .and_then(|(hx, hy)| self.get_block(hx, hy + 1))
+            .map(|block| !is_solid(block))
+            .unwrap_or(false)
     }
 
     pub fn set_spawn(&mut self, x: u32) -> Result<(), WorldError> {
@@ -544,6 +958,63 @@ impl World {
         Ok(players_loading)
     }
 
+    /// How many chunks currently have at least one player loading them, for the
+    /// [`crate::metrics::Metrics::loaded_chunks`] gauge.
+    pub fn loaded_chunk_count(&self) -> usize {
+        self.player_loaded
+            .iter()
+            .filter(|players_loading_chunk| !players_loading_chunk.is_empty())
+            .count()
+    }
+
+    /// The priority a background chunk generator should give `(chunk_x, chunk_y)`: squared
+    /// distance (in chunks) to whichever connected player's current chunk is closest, via
+    /// [`World::get_chunk_block_is_in`]. Lower means higher priority - a chunk right under a
+    /// player should materialize before one far away. `None` if no player is connected.
+    ///
+    /// Kept for a future background/streaming chunk generator: today every chunk is generated
+    /// eagerly up front (see [`World::generate`]), so nothing queues `(chunk_x, chunk_y)`
+    /// priorities yet - wiring that up for real means teaching every chunk accessor (`get_chunk`,
+    /// `get_block`, save/load, ...) to tolerate a chunk that hasn't finished generating, which is
+    /// a much bigger change than this helper alone.
+    #[allow(dead_code)]
+    pub(crate) fn chunk_priority(&self, chunk_x: u32, chunk_y: u32) -> Option<u32> {
+        self.players
+            .iter()
+            .filter_map(|conn| {
+                self.get_chunk_block_is_in(
+                    conn.server_player.x.max(0.0).round() as u32,
+                    conn.server_player.y.max(0.0).round() as u32,
+                )
+                .ok()
+            })
+            .map(|(player_chunk_x, player_chunk_y)| {
+                let dx = player_chunk_x as i64 - chunk_x as i64;
+                let dy = player_chunk_y as i64 - chunk_y as i64;
+                (dx * dx + dy * dy) as u32
+            })
+            .min()
+    }
+
+    /// Picks the closest-to-a-player (see [`World::chunk_priority`]) chunk coordinate out of
+    /// `pending` and removes it, so a caller streaming chunks in the background can dedupe
+    /// in-flight requests in `pending` itself and always dispatch the lowest-priority-number
+    /// entry next. Ties break on `HashMap` iteration order, which is unspecified but stable
+    /// enough within a single generation pass. Kept alongside [`World::chunk_priority`] for the
+    /// same not-yet-built streaming generator.
+    #[allow(dead_code)]
+    pub(crate) fn next_pending_chunk(
+        &self,
+        pending: &mut HashMap<(u32, u32), u32>,
+    ) -> Option<(u32, u32)> {
+        let next = pending
+            .iter()
+            .min_by_key(|(_, &priority)| priority)
+            .map(|(&pos, _)| pos)?;
+        pending.remove(&next);
+        Some(next)
+    }
+
     pub fn raw_set_block(
         &mut self,
         pos_x: u32,
@@ -590,24 +1061,69 @@ impl World {
 
     pub fn set_block(&mut self, pos_x: u32, pos_y: u32, block: Block) -> Result<(), WorldError> {
         self.raw_set_block(pos_x, pos_y, block)?;
-        // update block
         if block == Block::Water {
-            let SurroundingBlocks {
-                bottom,
-                left,
-                right,
-                ..
-            } = self.get_neighbours(pos_x, pos_y);
-            [bottom, left, right]
-                .into_iter()
-                .flatten()
-                .for_each(|(x, y, bl)| {
-                    if !is_solid(bl) && bl != Block::Water {
-                        self.to_update.insert((x, y, Block::Water));
-                    }
-                });
+            self.to_update.insert((pos_x, pos_y, Block::Water));
+        } else {
+            self.water_levels.remove(&(pos_x, pos_y));
+        }
+        self.relight_cell(pos_x, pos_y)?;
+        self.propagate_light()
+    }
+
+    /// Whether the water at `(x, y)` is an untouched source - terrain-placed lake/ocean water, or
+    /// a bucket placement - rather than water that's actively flowing. Sources have no entry in
+    /// [`World::water_levels`] at all: they're always full, and [`World::flow_water_cell`] lets
+    /// them donate to neighbours without ever depleting, the same way a Minecraft source block
+    /// keeps feeding a waterfall forever. Only finite, flowing water (an explicit `1..=7` entry)
+    /// can run dry.
+    fn is_water_source(&self, x: u32, y: u32) -> bool {
+        matches!(self.get_block(x, y), Ok(Block::Water)) && !self.water_levels.contains_key(&(x, y))
+    }
+
+    /// The fill level of the water at `(x, y)` - `0` if it isn't `Block::Water` at all. See
+    /// [`World::water_levels`].
+    fn water_level(&self, x: u32, y: u32) -> u8 {
+        match self.get_block(x, y) {
+            Ok(Block::Water) => self
+                .water_levels
+                .get(&(x, y))
+                .copied()
+                .unwrap_or(WATER_MAX_LEVEL),
+            _ => 0,
+        }
+    }
+
+    /// Writes `level` as the water fill at `(x, y)`, reverting to [`Block::Air`] at `0`. Goes
+    /// through [`World::set_block`] so the position is queued in `to_update` for its next
+    /// [`World::flow_water_cell`] pass whenever it's left holding water.
+    fn set_water_level(&mut self, x: u32, y: u32, level: u8) -> Result<(), WorldError> {
+        if level == 0 {
+            self.set_block(x, y, Block::Air)
+        } else {
+            self.water_levels.insert((x, y), level);
+            self.set_block(x, y, Block::Water)
+        }
+    }
+
+    /// The [`World::set_water_level`]/[`World::set_block_and_notify`] combination used by
+    /// [`World::tick_scheduled_blocks`] so players watching a flow settle see every level change,
+    /// not just the final one.
+    async fn set_water_level_and_notify(
+        &mut self,
+        to_web: ToWeb,
+        to_network: ToNetwork,
+        x: u32,
+        y: u32,
+        level: u8,
+    ) -> Result<(), WorldError> {
+        if level == 0 {
+            self.set_block_and_notify(to_web, to_network, x, y, Block::Air)
+                .await
+        } else {
+            self.water_levels.insert((x, y), level);
+            self.set_block_and_notify(to_web, to_network, x, y, Block::Water)
+                .await
         }
-        Ok(())
     }
 
     pub fn get_block(&self, pos_x: u32, pos_y: u32) -> Result<Block, WorldError> {
@@ -624,6 +1140,7 @@ impl World {
 
     pub async fn set_block_and_notify(
         &mut self,
+        to_web: ToWeb,
         to_network: ToNetwork,
         pos_x: u32,
         pos_y: u32,
@@ -634,7 +1151,7 @@ impl World {
         let players_loading = self.get_list_of_players_loading_chunk(chunk_x, chunk_y)?;
 
         players_loading.into_iter().for_each(|player| {
-            encode_and_send!(
+            encode_and_send_reliable!(
                 to_network,
                 PacketTypes::ServerUpdateBlock {
                     block: block.into(),
@@ -644,10 +1161,127 @@ impl World {
                 player.addr
             );
         });
+        let _ = to_web.send(WebEvent::BlockChange {
+            x: pos_x,
+            y: pos_y,
+            block,
+        });
 
         Ok(())
     }
 
+    fn queue_block_update(&mut self, pos_x: u32, pos_y: u32, block: Block) {
+        self.block_update_queue.push((pos_x, pos_y, block));
+    }
+
+    /// Fills the rectangle between `from` and `to` (inclusive, corners in any order) with `block`
+    /// in a single pass, queuing one batched network notification per affected chunk instead of
+    /// notifying players block-by-block. Returns the number of blocks changed.
+    pub fn fill_region(
+        &mut self,
+        from: (u32, u32),
+        to: (u32, u32),
+        block: Block,
+    ) -> Result<usize, WorldError> {
+        let (x1, x2) = (from.0.min(to.0), from.0.max(to.0));
+        let (y1, y2) = (from.1.min(to.1), from.1.max(to.1));
+        self.check_out_of_bounds_block(x2, y2)?;
+
+        let mut count = 0;
+        for y in y1..=y2 {
+            for x in x1..=x2 {
+                self.set_block(x, y, block)?;
+                self.queue_block_update(x, y, block);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Copies the rectangle between `src_from` and `src_to` (inclusive, corners in any order) to
+    /// `dest`, queuing one batched network notification per affected chunk instead of notifying
+    /// players block-by-block. Returns the number of blocks changed.
+    pub fn clone_region(
+        &mut self,
+        src_from: (u32, u32),
+        src_to: (u32, u32),
+        dest: (u32, u32),
+    ) -> Result<usize, WorldError> {
+        let (src_x1, src_x2) = (src_from.0.min(src_to.0), src_from.0.max(src_to.0));
+        let (src_y1, src_y2) = (src_from.1.min(src_to.1), src_from.1.max(src_to.1));
+        self.check_out_of_bounds_block(src_x2, src_y2)?;
+
+        let width = src_x2 - src_x1;
+        let height = src_y2 - src_y1;
+        self.check_out_of_bounds_block(dest.0 + width, dest.1 + height)?;
+
+        let mut source_blocks = Vec::with_capacity(((width + 1) * (height + 1)) as usize);
+        for y in src_y1..=src_y2 {
+            for x in src_x1..=src_x2 {
+                source_blocks.push(self.get_block(x, y)?);
+            }
+        }
+
+        let mut count = 0;
+        for (idx, block) in source_blocks.into_iter().enumerate() {
+            let dx = dest.0 + (idx as u32 % (width + 1));
+            let dy = dest.1 + (idx as u32 / (width + 1));
+            self.set_block(dx, dy, block)?;
+            self.queue_block_update(dx, dy, block);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub fn create_detached_inventory(&mut self, name: String) {
+        self.detached_inventories
+            .entry(name.clone())
+            .or_insert_with(|| DetachedInventory::new(name));
+    }
+
+    pub fn bind_detached_inventory(
+        &mut self,
+        name: &str,
+        owner: Option<u32>,
+    ) -> Result<(), WorldError> {
+        let inventory = self
+            .detached_inventories
+            .get_mut(name)
+            .ok_or_else(|| WorldError::NoSuchDetachedInventory(name.to_string()))?;
+        inventory.owner = owner;
+        Ok(())
+    }
+
+    /// Inserts `itemstack` into the detached inventory named `name` and, if it's bound to a
+    /// player, sends that one player a `ServerUpdateInventory` packet. Unlike player inventories,
+    /// this never gets broadcast to every connected player.
+    pub fn give_detached(
+        &mut self,
+        to_network: ToNetwork,
+        name: &str,
+        item: Item,
+        count: NonZeroU8,
+    ) -> Result<Result<(), u8>, WorldError> {
+        let inventory = self
+            .detached_inventories
+            .get_mut(name)
+            .ok_or_else(|| WorldError::NoSuchDetachedInventory(name.to_string()))?;
+        let insert_result = inventory.insert(ItemStack { item, count, damage: 0 });
+
+        if let Some(owner_id) = inventory.owner {
+            if let Some(owner) = self.players.iter().find(|p| p.id == owner_id) {
+                encode_and_send_reliable!(
+                    to_network,
+                    PacketTypes::ServerUpdateInventory {
+                        inv: inventory.slots.map(|stack| stack.map(|s| s.into())),
+                    },
+                    owner.addr
+                );
+            }
+        }
+        Ok(insert_result)
+    }
+
     pub async fn shutdown(
         &mut self,
         to_console: ToConsole,
@@ -660,6 +1294,7 @@ impl World {
             .for_each(|chunk| chunk.clear());
 
         self.players.iter_mut().for_each(|player| {
+            // Not worth retrying reliably - the network thread is torn down right after this.
             encode_and_send!(
                 to_network,
                 PacketTypes::ServerKick {
@@ -675,6 +1310,7 @@ impl World {
     pub async fn kick(
         &mut self,
         to_console: ToConsole,
+        to_web: ToWeb,
         to_network: ToNetwork,
         id: u32,
         msg: Option<&str>,
@@ -684,7 +1320,9 @@ impl World {
             Some(idx) => {
                 let connection = self.players.swap_remove(idx);
                 let kick_msg = msg.unwrap_or("No kick message provided");
+                let _ = to_web.send(WebEvent::PlayerLeave { id: connection.id });
                 self.unload_all_for(connection.id);
+                let _ = crate::trade::cancel(self, connection.id);
                 c_info!(
                     to_console,
                     "{} (addr: {}) kicked from sever! ({})",
@@ -707,7 +1345,7 @@ impl World {
                     )
                     .unwrap();
 
-                encode_and_send!(
+                encode_and_send_reliable!(
                     to_network,
                     PacketTypes::ServerKick {
                         msg: kick_msg.into(),
@@ -767,70 +1405,439 @@ impl World {
         })
     }
 
-    fn init_flow_water(&mut self) -> Result<(), WorldError> {
-        let water_to_update: HashSet<&(u32, u32, Block)> = self
-            .to_update
-            .par_iter()
-            .filter(|pos| pos.2 == Block::Water)
-            .collect();
+    /// Runs one cellular-automaton step for the water at `(x, y)`: pushes as much as fits
+    /// straight down first (stacking the cell below toward [`WATER_MAX_LEVEL`]), then splits
+    /// whatever's left equally between the left/right neighbours that are still lower, never
+    /// topping a neighbour up past `level - 1`. A cell that gives away everything it has reverts
+    /// to [`Block::Air`] - unless it's a source (see [`World::is_water_source`]), which never
+    /// depletes no matter how much it donates. No-ops if `(x, y)` isn't water anymore (already
+    /// drained by an earlier cell's step this same tick).
+    fn flow_water_cell(&mut self, x: u32, y: u32) -> Result<(), WorldError> {
+        if self.get_block(x, y)? != Block::Water {
+            return Ok(());
+        }
+        let is_source = self.is_water_source(x, y);
+        let mut level = self.water_level(x, y);
+
+        let SurroundingBlocks {
+            bottom, left, right, ..
+        } = self.get_neighbours(x, y);
+
+        if let Some((bx, by, bl)) = bottom {
+            if !is_solid(bl) {
+                let below_level = if bl == Block::Water {
+                    self.water_level(bx, by)
+                } else {
+                    0
+                };
+                let move_down = level.min(WATER_MAX_LEVEL - below_level);
+                if move_down > 0 {
+                    self.set_water_level(bx, by, below_level + move_down)?;
+                    if !is_source {
+                        level -= move_down;
+                    }
+                }
+            }
+        }
 
-        let to_update: HashSet<(u32, u32)> = water_to_update
-            .par_iter()
-            .flat_map(|&&(x, y, bl)| {
-                let SurroundingBlocks {
-                    bottom,
-                    left,
-                    right,
-                    ..
-                } = self.get_neighbours(x, y);
-                [bottom, left, right, Some((x, y, bl))]
+        let max_allowed = level.saturating_sub(1);
+        let recipients: Vec<(u32, u32, u8)> = [left, right]
+            .into_iter()
+            .flatten()
+            .filter(|&(_, _, bl)| !is_solid(bl))
+            .filter_map(|(nx, ny, bl)| {
+                let cur = if bl == Block::Water {
+                    self.water_level(nx, ny)
+                } else {
+                    0
+                };
+                (cur < max_allowed).then_some((nx, ny, cur))
             })
-            .filter_map(|maybe_block| {
-                if let Some((bl_x, bl_y, bl)) = maybe_block {
-                    if !is_solid(bl) {
-                        return Some((bl_x, bl_y));
+            .collect();
+
+        if !recipients.is_empty() {
+            let share = level / recipients.len() as u8;
+            for (nx, ny, cur) in recipients {
+                let give = share.min(max_allowed - cur);
+                if give > 0 {
+                    if !is_source {
+                        level -= give;
                     }
+                    self.set_water_level(nx, ny, cur + give)?;
                 }
-                None
+            }
+        }
+
+        if is_source {
+            Ok(())
+        } else {
+            self.set_water_level(x, y, level)
+        }
+    }
+
+    /// The [`World::flow_water_cell`] step, but surfacing every level change it makes through
+    /// `set_block_and_notify` so connected clients watch water settle instead of only seeing the
+    /// final state.
+    async fn flow_water_cell_and_notify(
+        &mut self,
+        to_web: ToWeb,
+        to_network: ToNetwork,
+        x: u32,
+        y: u32,
+    ) -> Result<(), WorldError> {
+        if self.get_block(x, y)? != Block::Water {
+            return Ok(());
+        }
+        let is_source = self.is_water_source(x, y);
+        let mut level = self.water_level(x, y);
+
+        let SurroundingBlocks {
+            bottom, left, right, ..
+        } = self.get_neighbours(x, y);
+
+        if let Some((bx, by, bl)) = bottom {
+            if !is_solid(bl) {
+                let below_level = if bl == Block::Water {
+                    self.water_level(bx, by)
+                } else {
+                    0
+                };
+                let move_down = level.min(WATER_MAX_LEVEL - below_level);
+                if move_down > 0 {
+                    self.set_water_level_and_notify(
+                        to_web.clone(),
+                        to_network.clone(),
+                        bx,
+                        by,
+                        below_level + move_down,
+                    )
+                    .await?;
+                    if !is_source {
+                        level -= move_down;
+                    }
+                }
+            }
+        }
+
+        let max_allowed = level.saturating_sub(1);
+        let recipients: Vec<(u32, u32, u8)> = [left, right]
+            .into_iter()
+            .flatten()
+            .filter(|&(_, _, bl)| !is_solid(bl))
+            .filter_map(|(nx, ny, bl)| {
+                let cur = if bl == Block::Water {
+                    self.water_level(nx, ny)
+                } else {
+                    0
+                };
+                (cur < max_allowed).then_some((nx, ny, cur))
             })
             .collect();
-        self.to_update.retain(|pos| pos.2 != Block::Water);
-        for (x, y) in to_update {
-            self.set_block(x, y, Block::Water)?;
+
+        if !recipients.is_empty() {
+            let share = level / recipients.len() as u8;
+            for (nx, ny, cur) in recipients {
+                let give = share.min(max_allowed - cur);
+                if give > 0 {
+                    if !is_source {
+                        level -= give;
+                    }
+                    self.set_water_level_and_notify(
+                        to_web.clone(),
+                        to_network.clone(),
+                        nx,
+                        ny,
+                        cur + give,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        if is_source {
+            Ok(())
+        } else {
+            self.set_water_level_and_notify(to_web, to_network, x, y, level)
+                .await
+        }
+    }
+
+    /// `(x, y)`'s level on `channel`, or `0` if it's never been lit.
+    fn channel_level(&self, x: u32, y: u32, channel: LightChannel) -> u8 {
+        let packed = self.light_levels.get(&(x, y)).copied().unwrap_or(0);
+        match channel {
+            LightChannel::Sky => packed >> 4,
+            LightChannel::Block => packed & 0x0F,
+        }
+    }
+
+    /// Writes `level` as `(x, y)`'s level on `channel`, leaving the other channel untouched.
+    /// Clears the entry out of `light_levels` entirely once both channels are back to `0`, same
+    /// as [`World::set_water_level`] reverting to `Block::Air` at `0`.
+    fn set_channel_level(&mut self, x: u32, y: u32, channel: LightChannel, level: u8) {
+        let sky = self.channel_level(x, y, LightChannel::Sky);
+        let block = self.channel_level(x, y, LightChannel::Block);
+        let (sky, block) = match channel {
+            LightChannel::Sky => (level, block),
+            LightChannel::Block => (sky, level),
+        };
+        if sky == 0 && block == 0 {
+            self.light_levels.remove(&(x, y));
+        } else {
+            self.light_levels.insert((x, y), (sky << 4) | block);
+        }
+    }
+
+    /// The brighter of `(x, y)`'s two light channels - what a client actually renders, since
+    /// skylight and block light combine rather than stack. Exposed to [`crate::network`] for
+    /// [`crate::network::NetworkChunk::with_light`].
+    pub fn light_at(&self, x: u32, y: u32) -> u8 {
+        self.channel_level(x, y, LightChannel::Sky)
+            .max(self.channel_level(x, y, LightChannel::Block))
+    }
+
+    /// Seeds skylight for the whole world: each column is scanned top-down (the same direction
+    /// [`World::get_highest_block_at`] scans, though this walks every column itself rather than
+    /// reusing that helper, since it also needs every lit cell's position, not just the topmost
+    /// one) and lit at [`LIGHT_MAX`] through non-solid blocks, stopping at the first solid one.
+    /// Queues every seeded cell so [`World::propagate_light`] can spread it sideways into
+    /// overhangs afterward.
+    fn seed_skylight(&mut self) -> Result<(), WorldError> {
+        for x in 0..self.width {
+            for y in (0..self.height).rev() {
+                if is_solid(self.get_block(x, y)?) {
+                    break;
+                }
+                self.set_channel_level(x, y, LightChannel::Sky, LIGHT_MAX);
+                self.light_queue
+                    .push_back(LightUpdate::Increase(x, y, LightChannel::Sky));
+            }
         }
         Ok(())
     }
 
-    async fn tick_water(&mut self, to_network: ToNetwork) -> Result<(), WorldError> {
-        let water_to_update: HashSet<&(u32, u32, Block)> = self
-            .to_update
-            .par_iter()
-            .filter(|pos| pos.2 == Block::Water)
-            .collect();
+    /// Seeds block light from every cell whose block has a non-zero [`light_emission`], queuing
+    /// each as an [`LightUpdate::Increase`] source for [`World::propagate_light`]. A no-op today
+    /// since no block emits light yet.
+    fn seed_block_light(&mut self) -> Result<(), WorldError> {
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let emission = light_emission(self.get_block(x, y)?);
+                if emission > 0 {
+                    self.set_channel_level(x, y, LightChannel::Block, emission);
+                    self.light_queue
+                        .push_back(LightUpdate::Increase(x, y, LightChannel::Block));
+                }
+            }
+        }
+        Ok(())
+    }
 
-        let to_update: HashSet<(u32, u32)> = water_to_update
-            .par_iter()
-            .flat_map(|&&(x, y, bl)| {
-                let SurroundingBlocks {
-                    bottom,
-                    left,
-                    right,
-                    ..
-                } = self.get_neighbours(x, y);
-                [bottom, left, right, Some((x, y, bl))]
-            })
-            .filter_map(|maybe_block| {
-                if let Some((bl_x, bl_y, bl)) = maybe_block {
-                    if !is_solid(bl) {
-                        return Some((bl_x, bl_y));
-                    }
+    /// Seeds both light channels from scratch and flood-fills them via [`World::propagate_light`].
+    /// Called once after a world is generated or loaded ([`World::generate`]/[`World::from_save`])
+    /// - light isn't part of [`crate::worldfile`]'s saved format (same reason `water_levels` isn't
+    /// either, see its doc comment), so a reloaded world relights itself from its blocks instead of
+    /// carrying light state across the round-trip.
+    fn init_light(&mut self) -> Result<(), WorldError> {
+        self.seed_skylight()?;
+        self.seed_block_light()?;
+        self.propagate_light()
+    }
+
+    /// Spreads `(x, y)`'s current level on `channel` one step to each non-solid neighbour (a
+    /// solid neighbour blocks the channel outright), at `level - 1`, skipping any neighbour
+    /// already equal or brighter - it already has as good a light path from elsewhere.
+    fn spread_light(&mut self, x: u32, y: u32, channel: LightChannel) -> Result<(), WorldError> {
+        let level = self.channel_level(x, y, channel);
+        if level == 0 {
+            return Ok(());
+        }
+        let next_level = level - 1;
+        let SurroundingBlocks {
+            top,
+            bottom,
+            left,
+            right,
+        } = self.get_neighbours(x, y);
+        for (nx, ny, block) in [top, bottom, left, right].into_iter().flatten() {
+            if is_solid(block) || next_level <= self.channel_level(nx, ny, channel) {
+                continue;
+            }
+            self.set_channel_level(nx, ny, channel, next_level);
+            self.light_queue
+                .push_back(LightUpdate::Increase(nx, ny, channel));
+        }
+        Ok(())
+    }
+
+    /// The removal counterpart of [`World::spread_light`]: `(x, y)`'s level on `channel` just
+    /// dropped from `old_level`, so any neighbour strictly dimmer than `old_level` was only lit by
+    /// it and gets darkened too (queued as a further `Decrease`); a neighbour already equal or
+    /// brighter has its own, independent source and is instead re-queued as an `Increase` so it
+    /// re-lights the gap just darkened.
+    fn unspread_light(
+        &mut self,
+        x: u32,
+        y: u32,
+        channel: LightChannel,
+        old_level: u8,
+    ) -> Result<(), WorldError> {
+        if old_level == 0 {
+            return Ok(());
+        }
+        let SurroundingBlocks {
+            top,
+            bottom,
+            left,
+            right,
+        } = self.get_neighbours(x, y);
+        for (nx, ny, _) in [top, bottom, left, right].into_iter().flatten() {
+            let neighbour_level = self.channel_level(nx, ny, channel);
+            if neighbour_level == 0 {
+                continue;
+            }
+            if neighbour_level < old_level {
+                self.set_channel_level(nx, ny, channel, 0);
+                self.light_queue
+                    .push_back(LightUpdate::Decrease(nx, ny, channel, neighbour_level));
+            } else {
+                self.light_queue
+                    .push_back(LightUpdate::Increase(nx, ny, channel));
+            }
+        }
+        Ok(())
+    }
+
+    /// Drains `light_queue` to completion, dispatching each [`LightUpdate`] to
+    /// [`World::spread_light`]/[`World::unspread_light`] - unlike [`World::init_scheduled_ticks`],
+    /// which drains one `to_update` snapshot per call, light updates enqueue more of themselves
+    /// as they spread, so this has to run until the queue itself goes dry.
+    fn propagate_light(&mut self) -> Result<(), WorldError> {
+        while let Some(update) = self.light_queue.pop_front() {
+            match update {
+                LightUpdate::Increase(x, y, channel) => self.spread_light(x, y, channel)?,
+                LightUpdate::Decrease(x, y, channel, old_level) => {
+                    self.unspread_light(x, y, channel, old_level)?
                 }
-                None
-            })
-            .collect();
-        self.to_update.retain(|pos| pos.2 != Block::Water);
-        for (x, y) in to_update {
-            self.set_block_and_notify(to_network.clone(), x, y, Block::Water)
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-derives both light channels at `(x, y)` after a block change there and queues whatever
+    /// [`World::propagate_light`] work follows. A newly solid block loses all its light outright
+    /// (queued as a `Decrease` from its old level); a newly non-solid one - or one whose
+    /// [`light_emission`] changed - is set to the brighter of its own emission and its
+    /// neighbours' levels minus one, then queued to spread from there ([`LightUpdate::Increase`]).
+    ///
+    /// This is a simplification of full Minecraft-style skylight beams: [`World::seed_skylight`]'s
+    /// initial top-down scan lights an open column at full strength all the way down, but digging
+    /// a shaft into already-generated terrain relights gradually as the flood-fill reaches it from
+    /// the sides, rather than instantly filling the whole new column - one flood-fill mechanism
+    /// serves both channels instead of keeping a separate beam-recompute pass for skylight alone.
+    fn relight_cell(&mut self, x: u32, y: u32) -> Result<(), WorldError> {
+        let block = self.get_block(x, y)?;
+        let solid = is_solid(block);
+        let SurroundingBlocks {
+            top,
+            bottom,
+            left,
+            right,
+        } = self.get_neighbours(x, y);
+        let neighbours: Vec<(u32, u32, Block)> =
+            [top, bottom, left, right].into_iter().flatten().collect();
+
+        for channel in [LightChannel::Sky, LightChannel::Block] {
+            let old_level = self.channel_level(x, y, channel);
+            let new_level = if solid {
+                0
+            } else {
+                let emission = match channel {
+                    LightChannel::Block => light_emission(block),
+                    LightChannel::Sky => 0,
+                };
+                let from_neighbours = neighbours
+                    .iter()
+                    .map(|&(nx, ny, _)| self.channel_level(nx, ny, channel))
+                    .max()
+                    .unwrap_or(0)
+                    .saturating_sub(1);
+                emission.max(from_neighbours)
+            };
+
+            if new_level == old_level {
+                continue;
+            }
+            self.set_channel_level(x, y, channel, new_level);
+            if new_level > old_level {
+                self.light_queue
+                    .push_back(LightUpdate::Increase(x, y, channel));
+            } else {
+                self.light_queue
+                    .push_back(LightUpdate::Decrease(x, y, channel, old_level));
+            }
+        }
+        Ok(())
+    }
+
+    /// The scheduled-tick behavior registered for `block` at `(x, y)` - the match arm a new
+    /// dynamic block (falling sand, a spreading sapling, ...) would add its own case to, the same
+    /// way [`define_blocks!`] registers each variant's static properties. Only `Block::Water`
+    /// reacts to a scheduled tick today, via [`World::flow_water_cell`]; anything else queued in
+    /// `to_update` (which shouldn't happen until a block actually registers a behavior here) is a
+    /// no-op.
+    fn run_scheduled_tick(&mut self, x: u32, y: u32, block: Block) -> Result<(), WorldError> {
+        match block {
+            Block::Water => self.flow_water_cell(x, y),
+            _ => Ok(()),
+        }
+    }
+
+    /// The notifying counterpart of [`World::run_scheduled_tick`], used by
+    /// [`World::tick_scheduled_blocks`] so a block's behavior can surface its changes to connected
+    /// players as they happen.
+    async fn run_scheduled_tick_and_notify(
+        &mut self,
+        to_web: ToWeb,
+        to_network: ToNetwork,
+        x: u32,
+        y: u32,
+        block: Block,
+    ) -> Result<(), WorldError> {
+        match block {
+            Block::Water => {
+                self.flow_water_cell_and_notify(to_web, to_network, x, y)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Drains `to_update` through [`World::run_scheduled_tick`], dispatching each entry by its
+    /// tagged block instead of assuming it's always water - same as
+    /// [`World::tick_scheduled_blocks`] but synchronous and without network notification. Used
+    /// during world generation, where [`World::generate_terrain`] just loops this until
+    /// `to_update` runs dry and every lake/spring has settled before the world is handed off.
+    fn init_scheduled_ticks(&mut self) -> Result<(), WorldError> {
+        let pending: Vec<(u32, u32, Block)> = std::mem::take(&mut self.to_update).into_iter().collect();
+        for (x, y, block) in pending {
+            self.run_scheduled_tick(x, y, block)?;
+        }
+        Ok(())
+    }
+
+    /// The runtime-tick counterpart of [`World::init_scheduled_ticks`]: drains `to_update` through
+    /// [`World::run_scheduled_tick_and_notify`] every `world_tick`, so adding a new scheduled
+    /// block type is just a new match arm there plus whatever queues it into `to_update` - nothing
+    /// in the tick loop itself needs to change. Water remains the only block that does this today.
+    async fn tick_scheduled_blocks(
+        &mut self,
+        to_web: ToWeb,
+        to_network: ToNetwork,
+    ) -> Result<(), WorldError> {
+        let pending: Vec<(u32, u32, Block)> = std::mem::take(&mut self.to_update).into_iter().collect();
+        for (x, y, block) in pending {
+            self.run_scheduled_tick_and_notify(to_web.clone(), to_network.clone(), x, y, block)
                 .await?;
         }
         Ok(())
@@ -973,6 +1980,12 @@ impl World {
         Ok(())
     }
 
+    /// Steps every connected player's movement for one physics tick. Both rayon passes below only
+    /// *read* `self` - gathering each player's [`Surrounding`] and computing their next [`Player`]
+    /// (`do_move`/`do_collision`, which also runs `do_fall`) are pure `(Player, Surrounding) ->
+    /// Player` transformations against a shared `&self` - so player threads never race on the
+    /// world or each other. All mutation (assigning `self.players`, queuing
+    /// `ServerPlayerUpdatePos`) happens afterward, back on this thread, in the plain `for` loop.
     pub async fn physics_tick(&mut self, to_network: ToNetwork) -> io::Result<Duration> {
         let now = Instant::now();
 
@@ -1037,15 +2050,54 @@ impl World {
         Ok(())
     }
 
+    /// Drains [`World::block_update_queue`], grouping the pending changes by receiver and block
+    /// type so each player gets one `ServerBatchUpdateBlock` per block type instead of one packet
+    /// per block changed.
+    pub async fn flush_block_queue(&mut self, to_network: ToNetwork) -> io::Result<()> {
+        let mut by_receiver: HashMap<SocketAddr, HashMap<Block, Vec<(u32, u32)>>> = HashMap::new();
+        for (x, y, block) in self.block_update_queue.drain(..) {
+            let Ok((chunk_x, chunk_y)) = self.get_chunk_block_is_in(x, y) else {
+                continue;
+            };
+            let Ok(players_loading) = self.get_list_of_players_loading_chunk(chunk_x, chunk_y)
+            else {
+                continue;
+            };
+            for player in players_loading {
+                by_receiver
+                    .entry(player.addr)
+                    .or_default()
+                    .entry(block)
+                    .or_default()
+                    .push((x, y));
+            }
+        }
+
+        for (addr, by_block) in by_receiver {
+            for (block, batch) in by_block {
+                encode_and_send_reliable!(
+                    to_network,
+                    PacketTypes::ServerBatchUpdateBlock {
+                        block: block.into(),
+                        batch,
+                    },
+                    addr
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub async fn world_tick(
         &mut self,
         to_console: ToConsole,
+        to_web: ToWeb,
         to_network: ToNetwork,
     ) -> io::Result<Duration> {
         let now = Instant::now();
 
-        if let Err(e) = self.tick_water(to_network).await {
-            c_error!(to_console, "Error occurred while ticking water: {e}")
+        if let Err(e) = self.tick_scheduled_blocks(to_web, to_network).await {
+            c_error!(to_console, "Error occurred while ticking scheduled blocks: {e}")
         };
 
         let time = now.elapsed();
@@ -1059,29 +2111,149 @@ impl Chunk {
             size,
             chunk_x,
             chunk_y,
-            blocks: (0..size.pow(2))
-                .into_par_iter()
-                .map(|_| Block::Air)
-                .collect(),
+            storage: ChunkStorage::Uniform(Block::Air),
         }
     }
 
     fn set_block(&mut self, chunk_pos_x: u32, chunk_pos_y: u32, block: Block) -> &mut Self {
         let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
-        self.blocks[idx] = block;
+        let cell_count = (self.size * self.size) as usize;
+        match &mut self.storage {
+            ChunkStorage::Uniform(existing) if *existing == block => {}
+            ChunkStorage::Uniform(existing) => {
+                let palette = vec![*existing, block];
+                let bits_per_entry = bits_needed(palette.len());
+                let mut indices = vec![0u8; packed_byte_len(cell_count, bits_per_entry)];
+                set_packed_index(&mut indices, bits_per_entry, idx, 1);
+                self.storage = ChunkStorage::Paletted {
+                    palette,
+                    bits_per_entry,
+                    indices,
+                };
+            }
+            ChunkStorage::Paletted {
+                palette,
+                bits_per_entry,
+                indices,
+            } => {
+                let palette_index = match palette.iter().position(|&b| b == block) {
+                    Some(pos) => pos,
+                    None => {
+                        palette.push(block);
+                        palette.len() - 1
+                    }
+                };
+                let needed_bits = bits_needed(palette.len());
+                if needed_bits != *bits_per_entry {
+                    let mut grown = vec![0u8; packed_byte_len(cell_count, needed_bits)];
+                    for cell in 0..cell_count {
+                        let value = get_packed_index(indices, *bits_per_entry, cell);
+                        set_packed_index(&mut grown, needed_bits, cell, value);
+                    }
+                    *indices = grown;
+                    *bits_per_entry = needed_bits;
+                }
+                set_packed_index(indices, *bits_per_entry, idx, palette_index as u32);
+            }
+        }
         self
     }
 
     fn get_block(&self, chunk_pos_x: u32, chunk_pos_y: u32) -> Block {
-        self.blocks[(chunk_pos_y * self.size + chunk_pos_x) as usize]
+        let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
+        match &self.storage {
+            ChunkStorage::Uniform(block) => *block,
+            ChunkStorage::Paletted {
+                palette,
+                bits_per_entry,
+                indices,
+            } => palette[get_packed_index(indices, *bits_per_entry, idx) as usize],
+        }
+    }
+
+    /// Expands this chunk's storage into one `Block` per cell, for call sites (like
+    /// `NetworkChunk`'s wire format) that need the full per-cell layout rather than the palette.
+    pub(crate) fn into_blocks(self) -> Vec<Block> {
+        let cell_count = (self.size * self.size) as usize;
+        match self.storage {
+            ChunkStorage::Uniform(block) => vec![block; cell_count],
+            ChunkStorage::Paletted {
+                palette,
+                bits_per_entry,
+                indices,
+            } => (0..cell_count)
+                .into_par_iter()
+                .map(|cell| palette[get_packed_index(&indices, bits_per_entry, cell) as usize])
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod chunk_storage_tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// Writes `cell_count` random blocks into a fresh chunk via `set_block`, reading each one back
+    /// via `get_block` right after and against an oracle `Vec<Block>` at the end - covering the
+    /// `Uniform` chunk, the initial `Uniform` -> `Paletted` transition, and every `bits_per_entry`
+    /// growth in between (the full `Block` palette needs 4 bits, so this crosses the 1/2/3/4-bit
+    /// boundaries too).
+    #[test]
+    fn set_get_round_trips_through_every_palette_width() {
+        let size = 8;
+        let cell_count = (size * size) as usize;
+        let blocks: Vec<Block> = Block::iter().collect();
+        let mut rng = SmallRng::seed_from_u64(0xC4A1);
+        let mut chunk = Chunk::empty(size, 0, 0);
+        let mut oracle = vec![Block::Air; cell_count];
+
+        for idx in 0..cell_count {
+            let block = blocks[rng.random_range(0..blocks.len())];
+            let (x, y) = (idx as u32 % size, idx as u32 / size);
+            chunk.set_block(x, y, block);
+            oracle[idx] = block;
+            assert_eq!(chunk.get_block(x, y), block, "mismatch right after set_block at ({x}, {y})");
+        }
+
+        for idx in 0..cell_count {
+            let (x, y) = (idx as u32 % size, idx as u32 / size);
+            assert_eq!(chunk.get_block(x, y), oracle[idx], "mismatch on final read-back at ({x}, {y})");
+        }
+    }
+
+    /// A chunk that's never written to stays `Uniform` with no index storage at all, and reads
+    /// back as the block it was created with everywhere.
+    #[test]
+    fn untouched_chunk_is_uniform_air() {
+        let chunk = Chunk::empty(4, 0, 0);
+        assert!(matches!(chunk.storage, ChunkStorage::Uniform(Block::Air)));
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(chunk.get_block(x, y), Block::Air);
+            }
+        }
+    }
+
+    /// Setting a cell to the block it already has shouldn't grow the palette - `Uniform` stays
+    /// `Uniform`.
+    #[test]
+    fn setting_same_block_stays_uniform() {
+        let mut chunk = Chunk::empty(4, 0, 0);
+        chunk.set_block(1, 1, Block::Air);
+        assert!(matches!(chunk.storage, ChunkStorage::Uniform(Block::Air)));
     }
 }
 
 define_blocks! {
-    Air = 0 => { solid: false, item: None, hardness: 0 },
-    Grass = 1 => { solid: true, item: Some(Item::Grass), hardness: 0 },
-    Stone = 2 => { solid: true, item: Some(Item::Stone), hardness: 1 },
-    Wood = 3 => { solid: true, item: Some(Item::Wood), hardness: 0 },
-    Leaves = 4 => { solid: true, item: Some(Item::Leaves), hardness: 0},
-    Water = 5 => { solid: false, item: Some(Item::WaterBucket), hardness: 0},
+    Air = 0 => { solid: false, item: None, hardness: 0, light_emission: 0 },
+    Grass = 1 => { solid: true, item: Some(Item::Grass), hardness: 0, light_emission: 0 },
+    Stone = 2 => { solid: true, item: Some(Item::Stone), hardness: 1, light_emission: 0 },
+    Wood = 3 => { solid: true, item: Some(Item::Wood), hardness: 0, light_emission: 0 },
+    Leaves = 4 => { solid: true, item: Some(Item::Leaves), hardness: 0, light_emission: 0},
+    Water = 5 => { solid: false, item: Some(Item::WaterBucket), hardness: 0, light_emission: 0},
+    Sand = 6 => { solid: true, item: None, hardness: 0, light_emission: 0 },
+    Dirt = 7 => { solid: true, item: None, hardness: 0, light_emission: 0 },
+    Lava = 8 => { solid: false, item: None, hardness: 0, light_emission: 0 },
+    Ladder = 9 => { solid: false, item: None, hardness: 0, light_emission: 0 },
 }
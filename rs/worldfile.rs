@@ -0,0 +1,140 @@
+use crate::world::{Chunk, World};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"YCWF";
+const FORMAT_VERSION: u8 = 1;
+
+#[derive(Error, Debug)]
+pub enum WorldFileError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("(de)serialization error: {0}")]
+    Serde(#[from] serde_pickle::Error),
+    #[error("not a yourcraft world file")]
+    BadMagic,
+    #[error("unsupported world file format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("corrupt world file: {0}")]
+    CorruptSave(String),
+}
+
+/// The header fields and decompressed chunk grid read back from a world file by [`load`].
+pub struct LoadedWorld {
+    pub width: u32,
+    pub height: u32,
+    pub chunk_size: u32,
+    pub spawn_point: u32,
+    pub spawn_range: u32,
+    pub seed: Option<u64>,
+    pub chunks: Vec<Chunk>,
+}
+
+fn header_bytes(world: &World) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    header.extend_from_slice(&world.width.to_le_bytes());
+    header.extend_from_slice(&world.height.to_le_bytes());
+    header.extend_from_slice(&world.chunk_size.to_le_bytes());
+    header.extend_from_slice(&world.spawn_point.to_le_bytes());
+    header.extend_from_slice(&world.spawn_range.get().to_le_bytes());
+    match world.generation_seed {
+        Some(seed) => {
+            header.push(1);
+            header.extend_from_slice(&seed.to_le_bytes());
+        }
+        None => header.push(0),
+    }
+    header
+}
+
+/// Writes `world` to `path`: a small header (magic bytes, format version, dimensions, spawn
+/// point/range, generation seed) followed by the block grid, pickled and gzip-compressed so
+/// large worlds stay compact on disk.
+pub fn save(world: &World, path: &Path) -> Result<(), WorldFileError> {
+    let chunk_bytes = serde_pickle::to_vec(&world.chunks, serde_pickle::SerOptions::new())?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&chunk_bytes)?;
+    let compressed = encoder.finish()?;
+
+    let mut file_bytes = header_bytes(world);
+    file_bytes.extend_from_slice(&compressed);
+    fs::write(path, file_bytes)?;
+    Ok(())
+}
+
+/// Reads a world file written by [`save`] back into its header fields and chunk grid.
+pub fn load(path: &Path) -> Result<LoadedWorld, WorldFileError> {
+    let bytes = fs::read(path)?;
+    let mut cursor = &bytes[..];
+
+    fn take<'a>(cursor: &mut &'a [u8], n: usize) -> io::Result<&'a [u8]> {
+        if cursor.len() < n {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated world file",
+            ));
+        }
+        let (head, tail) = cursor.split_at(n);
+        *cursor = tail;
+        Ok(head)
+    }
+    macro_rules! read_u32 {
+        ($cursor: expr) => {
+            u32::from_le_bytes(take(&mut $cursor, 4)?.try_into().unwrap_or_else(|_| unreachable!()))
+        };
+    }
+
+    if take(&mut cursor, 4)? != MAGIC {
+        return Err(WorldFileError::BadMagic);
+    }
+    let version = take(&mut cursor, 1)?[0];
+    if version != FORMAT_VERSION {
+        return Err(WorldFileError::UnsupportedVersion(version));
+    }
+
+    let width = read_u32!(cursor);
+    let height = read_u32!(cursor);
+    let chunk_size = read_u32!(cursor);
+    let spawn_point = read_u32!(cursor);
+    let spawn_range = read_u32!(cursor);
+    if chunk_size == 0 || width % chunk_size != 0 || height % chunk_size != 0 {
+        return Err(WorldFileError::CorruptSave(format!(
+            "world is {width}x{height} but doesn't divide evenly into {chunk_size}-sized chunks"
+        )));
+    }
+
+    let seed = if take(&mut cursor, 1)?[0] != 0 {
+        Some(u64::from_le_bytes(
+            take(&mut cursor, 8)?.try_into().unwrap_or_else(|_| unreachable!()),
+        ))
+    } else {
+        None
+    };
+
+    let mut decoder = GzDecoder::new(cursor);
+    let mut chunk_bytes = Vec::new();
+    decoder.read_to_end(&mut chunk_bytes)?;
+    let chunks: Vec<Chunk> = serde_pickle::from_slice(&chunk_bytes, serde_pickle::DeOptions::new())?;
+    let expected_chunks = (width / chunk_size) * (height / chunk_size);
+    if chunks.len() as u32 != expected_chunks {
+        return Err(WorldFileError::CorruptSave(format!(
+            "expected {expected_chunks} chunks for a {width}x{height} world, found {}",
+            chunks.len()
+        )));
+    }
+
+    Ok(LoadedWorld {
+        width,
+        height,
+        chunk_size,
+        spawn_point,
+        spawn_range,
+        seed,
+        chunks,
+    })
+}
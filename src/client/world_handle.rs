@@ -0,0 +1,57 @@
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::sync::mpsc::Receiver;
+use std::thread;
+
+use crate::world::WorldEvent;
+
+/// A handle to a [`World`](crate::world::World)'s event stream, exposed to the embedded Python
+/// client so it can register a callback and receive incremental block-change and full-chunk
+/// messages instead of polling the whole world.
+#[pyclass]
+pub struct WorldHandle {
+    events: Option<Receiver<WorldEvent>>,
+}
+
+impl WorldHandle {
+    pub fn new(events: Receiver<WorldEvent>) -> WorldHandle {
+        WorldHandle {
+            events: Some(events),
+        }
+    }
+}
+
+#[pymethods]
+impl WorldHandle {
+    /// Spawns a background thread that forwards every `WorldEvent` to `callback` until the
+    /// world's sender half is dropped, calling it as either `callback("block_change", x, y,
+    /// block_raw)` or `callback("chunk_update", chunk_x, chunk_y, data)`.
+    fn register_callback(&mut self, callback: Py<PyAny>) -> PyResult<()> {
+        let events = self
+            .events
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("a callback is already registered"))?;
+
+        thread::spawn(move || {
+            for event in events {
+                Python::with_gil(|py| {
+                    let result = match event {
+                        WorldEvent::BlockChange { x, y, block_raw } => {
+                            callback.call1(py, ("block_change", x, y, block_raw))
+                        }
+                        WorldEvent::ChunkUpdate {
+                            chunk_x,
+                            chunk_y,
+                            data,
+                        } => callback.call1(py, ("chunk_update", chunk_x, chunk_y, data)),
+                    };
+                    if let Err(err) = result {
+                        err.print(py);
+                    }
+                });
+            }
+        });
+
+        Ok(())
+    }
+}
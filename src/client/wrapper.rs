@@ -5,10 +5,23 @@ use std::ffi::CString;
 use std::fs::read_to_string;
 use std::path::Path;
 
+mod world_handle;
+#[path = "../world.rs"]
+mod world;
+
+use world::World;
+use world_handle::WorldHandle;
+
 fn main() -> PyResult<()> {
     let python_path = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src/client/py"));
     let main = CString::new(read_to_string(python_path.join("main.py"))?)?;
 
+    // TODO: this should come from the server's `ServerSync` packet once the client actually
+    // connects over the network; a fresh empty world is a placeholder so `main.py` has something
+    // to subscribe to.
+    let mut world = World::generate_empty(8, 8, 2, 0).expect("default world parameters are valid");
+    let handle = WorldHandle::new(world.subscribe());
+
     prepare_freethreaded_python();
     Python::with_gil(|py| -> PyResult<Py<PyAny>> {
         let py_path = py.import("sys")?.getattr("path")?;
@@ -17,7 +30,7 @@ fn main() -> PyResult<()> {
         let app: Py<PyAny> = PyModule::from_code(py, &main, c"", c"")?
             .getattr("main")?
             .into();
-        app.call0(py)
+        app.call1(py, (handle,))
     })?;
     Ok(())
 }
\ No newline at end of file
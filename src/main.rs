@@ -11,7 +11,7 @@ mod player;
 async fn main() -> io::Result<()> {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
 
-    let mut world = World::generate_empty(8, 8, 2).unwrap();
+    let mut world = World::generate_empty(8, 8, 2, 0).unwrap();
     world.set_block(3, 2, Block::Grass).unwrap();
     
     let socket = UdpSocket::bind("0.0.0.0:8475").await?;
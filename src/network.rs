@@ -55,13 +55,9 @@ macro_rules! define_packets {
             Invalid = 0,
             $($name = $value),*
         }
-
         impl Into<u8> for PacketTypes {
-            fn into(self) -> u8 {
-                self as u8
-            }
+            fn into(self) -> u8 { self as u8 }
         }
-
         impl Into<PacketTypes> for u8 {
             fn into(self) -> PacketTypes {
                 match self {
@@ -70,7 +66,6 @@ macro_rules! define_packets {
                 }
             }
         }
-
         $(
             #[derive(Serialize, Deserialize, Debug)]
             pub struct $struct {
@@ -82,60 +77,19 @@ macro_rules! define_packets {
 
 // Use the macro to define packets
 define_packets!(
-    ClientHello = 1 => ClientHello {
-        name: String
-    },
-    ServerSync = 2 => ServerSync {
-        player_id: u32,
-        world_width: u32,
-        world_height: u32,
-        chunk_size: u32
-    },
-    ClientRequestChunk = 3 => ClientRequestChunk {
-        chunk_coords_x: u32,
-        chunk_coords_y: u32,
-    },
-    ServerChunkResponse = 4 => ServerChunkResponse {
-        chunk: Chunk,
-    },
-    ClientUnloadChunk = 5 => ClientUnloadChunk {
-        chunk_coords_x: u32,
-        chunk_coords_y: u32,
-    },
-    ServerPlayerJoin = 6 => ServerPlayerJoin {
-        player_name: String,
-        player_id: u32
-    },
-    ServerPlayerEnterLoaded = 7 => ServerPlayerEnterLoaded {
-        player_name: String,
-        player_id: u32
-    },
-    ServerPlayerLeaveLoaded = 8 => ServerPlayerLeaveLoaded {
-        player_name: String,
-        player_id: u32
-    },
-    ServerPlayerLeave = 9 => ServerPlayerLeave {
-        player_name: String,
-        player_id: u32
-    },
+    ClientHello = 1 => ClientHello { name: String },
+    ServerSync = 2 => ServerSync { player_id: u32, world_width: u32, world_height: u32, chunk_size: u32 },
+    ClientRequestChunk = 3 => ClientRequestChunk { chunk_coords_x: u32, chunk_coords_y: u32 },
+    ServerChunkResponse = 4 => ServerChunkResponse { chunk: Chunk },
+    ClientUnloadChunk = 5 => ClientUnloadChunk { chunk_coords_x: u32, chunk_coords_y: u32 },
+    ServerPlayerJoin = 6 => ServerPlayerJoin { player_name: String, player_id: u32 },
+    ServerPlayerEnterLoaded = 7 => ServerPlayerEnterLoaded { player_name: String, player_id: u32 },
+    ServerPlayerLeaveLoaded = 8 => ServerPlayerLeaveLoaded { player_name: String, player_id: u32 },
+    ServerPlayerLeave = 9 => ServerPlayerLeave { player_name: String, player_id: u32 },
     ClientGoodbye = 10 => ClientGoodbye {},
-    ClientPlaceBlock = 11 => ClientPlaceBlock {
-        block: Block,
-        x: u32,
-        y: u32
-    },
-    ServerUpdateBlock = 12 => ServerUpdateBlock {
-        block: Block,
-        x: u32,
-        y: u32
-    },
-    ClientPlayerMoveX = 13 => ClientPlayerMoveX {
-        pos_x: f32
-    },
+    ClientPlaceBlock = 11 => ClientPlaceBlock { block: Block, x: u32, y: u32 },
+    ServerUpdateBlock = 12 => ServerUpdateBlock { block: Block, x: u32, y: u32 },
+    ClientPlayerMoveX = 13 => ClientPlayerMoveX { pos_x: f32 },
     ClientPlayerJump = 14 => ClientPlayerJump {},
-    ServerPlayerUpdatePos = 15 => ServerPlayerUpdatePos {
-        player_id: u32,
-        pos_x: f32,
-        pos_y: f32
-    }
+    ServerPlayerUpdatePos = 15 => ServerPlayerUpdatePos { player_id: u32, pos_x: f32, pos_y: f32 }
 );
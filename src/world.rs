@@ -1,10 +1,23 @@
 use log::debug;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 #[derive(Debug, Clone)]
 pub enum WorldError {
     MismatchedChunkSize,
     GroundLevelOverWorldHeight,
-    OutOfBounds(u32, u32),
+    OutOfBounds(i32, i32),
+}
+
+/// A change to report to anything watching a [`World`] via [`World::subscribe`]: either a single
+/// block edit or a whole chunk being made available (e.g. on first load), encoded with
+/// [`Chunk::encode`] so it can be forwarded without depending on `World`'s internals.
+#[derive(Debug, Clone)]
+pub enum WorldEvent {
+    BlockChange { x: u32, y: i32, block_raw: u16 },
+    ChunkUpdate { chunk_x: u32, chunk_y: i32, data: Vec<u8> },
 }
 
 #[derive(Debug)]
@@ -12,136 +25,702 @@ pub struct World {
     pub width: u32,
     pub height: u32,
     pub chunk_size: u32,
-    width_chunks: u32,
-    height_chunks: u32,
-    pub chunks: Vec<Chunk>,
+    /// The y coordinate of the lowest block in the world, e.g. `-64` for a world with an
+    /// underground region below the origin. Block and chunk y coordinates are signed and are
+    /// translated by this value before indexing into chunk storage.
+    pub min_y: i32,
+    chunks: ChunkStorage,
+    /// Senders handed out by [`World::subscribe`]. A dead receiver (its `Sender` failing to
+    /// send) is dropped the next time an event is emitted.
+    listeners: Vec<Sender<WorldEvent>>,
 }
 
+/// Holds chunks sparsely, keyed by chunk coordinates, and only materializes one on first write.
+/// Reads of a chunk that was never written to are served from a shared empty chunk instead of
+/// allocating, so a world's memory cost tracks how much of it has actually been touched rather
+/// than its declared dimensions.
 #[derive(Debug)]
+struct ChunkStorage {
+    chunk_size: u32,
+    /// The chunk y coordinate of `min_y`, subtracted from a chunk's y coordinate to get a
+    /// non-negative key into `chunks`.
+    chunk_y_floor: i32,
+    chunks: HashMap<(u32, u32), Chunk>,
+    empty: Chunk,
+}
+
+impl ChunkStorage {
+    fn new(chunk_size: u32, min_y: i32) -> ChunkStorage {
+        let chunk_y_floor = min_y.div_euclid(chunk_size as i32);
+        ChunkStorage {
+            chunk_size,
+            chunk_y_floor,
+            chunks: HashMap::new(),
+            empty: Chunk::empty(chunk_size, 0, 0),
+        }
+    }
+
+    fn key(&self, chunk_x: u32, chunk_y: i32) -> (u32, u32) {
+        (chunk_x, (chunk_y - self.chunk_y_floor) as u32)
+    }
+
+    fn get_chunk(&self, chunk_x: u32, chunk_y: i32) -> &Chunk {
+        self.chunks
+            .get(&self.key(chunk_x, chunk_y))
+            .unwrap_or(&self.empty)
+    }
+
+    fn get_chunk_mut(&mut self, chunk_x: u32, chunk_y: i32) -> &mut Chunk {
+        let chunk_size = self.chunk_size;
+        let key = self.key(chunk_x, chunk_y);
+        self.chunks
+            .entry(key)
+            .or_insert_with(|| Chunk::empty(chunk_size, chunk_x, chunk_y))
+    }
+
+    fn insert(&mut self, chunk_x: u32, chunk_y: i32, chunk: Chunk) {
+        let key = self.key(chunk_x, chunk_y);
+        self.chunks.insert(key, chunk);
+    }
+}
+
+/// A chunk's blocks are stored as a palette of the distinct blocks present plus a bit-packed
+/// array of indices into that palette, mirroring the paletted containers used by most voxel
+/// engines. `data` is `None` while the chunk is a single uniform block (e.g. freshly generated
+/// air), which avoids allocating an index array for the common case.
+#[derive(Debug, Clone)]
 pub struct Chunk {
     pub size: u32,
     pub chunk_x: u32,
-    pub chunk_y: u32,
-    pub blocks: Vec<Block>,
+    pub chunk_y: i32,
+    palette: Vec<Block>,
+    bits_per_entry: u32,
+    data: Option<Vec<u64>>,
+}
+
+/// Smallest number of bits needed to index `palette_len` distinct values, i.e.
+/// `max(1, ceil(log2(palette_len)))`.
+fn bits_per_entry_for(palette_len: usize) -> u32 {
+    if palette_len <= 1 {
+        1
+    } else {
+        (usize::BITS - (palette_len - 1).leading_zeros()).max(1)
+    }
+}
+
+fn words_for(entries: u32, bits_per_entry: u32) -> usize {
+    ((entries as u64 * bits_per_entry as u64 + 63) / 64) as usize
+}
+
+fn mask_for(bits_per_entry: u32) -> u64 {
+    if bits_per_entry >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits_per_entry) - 1
+    }
+}
+
+fn get_packed(data: &[u64], bits_per_entry: u32, index: usize) -> u32 {
+    let bit_pos = index as u64 * bits_per_entry as u64;
+    let word_idx = (bit_pos / 64) as usize;
+    let bit_off = (bit_pos % 64) as u32;
+    let mask = mask_for(bits_per_entry);
+
+    let low = data[word_idx] >> bit_off;
+    let value = if bit_off + bits_per_entry <= 64 {
+        low
+    } else {
+        let high_bits = bit_off + bits_per_entry - 64;
+        let high = data[word_idx + 1] & ((1u64 << high_bits) - 1);
+        low | (high << (64 - bit_off))
+    };
+    (value & mask) as u32
 }
 
+fn set_packed(data: &mut [u64], bits_per_entry: u32, index: usize, value: u32) {
+    let bit_pos = index as u64 * bits_per_entry as u64;
+    let word_idx = (bit_pos / 64) as usize;
+    let bit_off = (bit_pos % 64) as u32;
+    let mask = mask_for(bits_per_entry);
+    let value = value as u64 & mask;
+
+    data[word_idx] = (data[word_idx] & !(mask << bit_off)) | (value << bit_off);
+    if bit_off + bits_per_entry > 64 {
+        let high_bits = bit_off + bits_per_entry - 64;
+        let high_mask = (1u64 << high_bits) - 1;
+        data[word_idx + 1] = (data[word_idx + 1] & !high_mask) | (value >> (64 - bit_off));
+    }
+}
+
+/// Re-encodes `entries` packed values from `old_bits` to `new_bits` per entry.
+fn repack(data: &[u64], old_bits: u32, new_bits: u32, entries: u32) -> Vec<u64> {
+    let mut repacked = vec![0u64; words_for(entries, new_bits)];
+    for i in 0..entries as usize {
+        let value = get_packed(data, old_bits, i);
+        set_packed(&mut repacked, new_bits, i, value);
+    }
+    repacked
+}
+
+/// Deterministically builds one chunk of procedural terrain. Run on a worker thread by
+/// `World::generate_terrain`; takes no state beyond its arguments so it can run independently of
+/// every other chunk.
+fn generate_terrain_chunk(
+    chunk_x: u32,
+    chunk_y: u32,
+    chunk_size: u32,
+    ground_level: u32,
+    seed: u64,
+) -> Chunk {
+    let mut chunk = Chunk::empty(chunk_size, chunk_x, chunk_y as i32);
+    let base_x = chunk_x * chunk_size;
+    let base_y = chunk_y * chunk_size;
+
+    for local_x in 0..chunk_size {
+        let world_x = base_x + local_x;
+        let surface = column_height(seed, ground_level, world_x);
+
+        for local_y in 0..chunk_size {
+            let world_y = base_y + local_y;
+            if world_y + 1 < surface {
+                chunk.set_block(local_x, local_y, Block::Stone);
+            } else if world_y + 1 == surface {
+                chunk.set_block(local_x, local_y, Block::Grass);
+            } else if world_y < ground_level {
+                chunk.set_block(local_x, local_y, Block::Water { level: Level(7) });
+            }
+            // Otherwise leave the block as the chunk's default Air.
+        }
+
+        if surface > ground_level && should_spawn_tree(seed, world_x) {
+            let grass_local_y = surface - 1 - base_y;
+            stamp_tree(&mut chunk, local_x, grass_local_y, chunk_size);
+        }
+    }
+
+    chunk
+}
+
+/// The height (number of solid blocks) of the terrain column at `world_x`, wobbling around
+/// `ground_level` according to a value-noise curve seeded by `seed`.
+fn column_height(seed: u64, ground_level: u32, world_x: u32) -> u32 {
+    const AMPLITUDE: f64 = 12.0;
+    const FREQUENCY: f64 = 0.05;
+
+    let noise = value_noise(seed, world_x as f64 * FREQUENCY);
+    let offset = (noise * AMPLITUDE).round() as i64;
+    (ground_level as i64 + offset).max(1) as u32
+}
+
+/// Whether a tree should be rooted at `world_x`, decided by hashing the seed and column together
+/// so it's deterministic without needing to track state across columns.
+fn should_spawn_tree(seed: u64, world_x: u32) -> bool {
+    const ONE_IN: u64 = 12;
+    hash_u64(seed ^ (world_x as u64).wrapping_mul(0xA24B_AED4_963E_E407)).is_multiple_of(ONE_IN)
+}
+
+/// Stamps a small trunk-and-canopy tree rooted just above `grass_local_y` at `local_x`. Skipped
+/// if the canopy would spill outside this chunk, since each chunk is generated independently.
+fn stamp_tree(chunk: &mut Chunk, local_x: u32, grass_local_y: u32, chunk_size: u32) {
+    const TRUNK_HEIGHT: u32 = 3;
+
+    if local_x == 0
+        || local_x + 2 >= chunk_size
+        || grass_local_y + TRUNK_HEIGHT + 2 >= chunk_size
+    {
+        return;
+    }
+
+    for i in 1..=TRUNK_HEIGHT {
+        chunk.set_block(
+            local_x,
+            grass_local_y + i,
+            Block::Log {
+                axis: Axis::Vertical,
+            },
+        );
+    }
+
+    let canopy_y = grass_local_y + TRUNK_HEIGHT;
+    for dx in 0..3u32 {
+        for dy in 0..2u32 {
+            chunk.set_block(local_x + dx - 1, canopy_y + dy, Block::Leaves);
+        }
+    }
+    chunk.set_block(local_x, canopy_y + 2, Block::Leaves);
+}
+
+/// A deterministic, dependency-free value-noise sample at `x`: random values are assigned to
+/// each integer lattice point (via `seed`) and smoothly interpolated between them.
+fn value_noise(seed: u64, x: f64) -> f64 {
+    let x0 = x.floor() as i64;
+    let x1 = x0 + 1;
+    let t = x - x0 as f64;
+    let t = t * t * (3.0 - 2.0 * t);
+
+    let v0 = lattice_value(seed, x0);
+    let v1 = lattice_value(seed, x1);
+    v0 + t * (v1 - v0)
+}
+
+/// A pseudo-random value in `-1.0..=1.0` for the integer lattice point `x`.
+fn lattice_value(seed: u64, x: i64) -> f64 {
+    let h = hash_u64(seed ^ (x as u64).wrapping_mul(0x2545_F491_4F6C_DD1D));
+    (h as f64 / u64::MAX as f64) * 2.0 - 1.0
+}
+
+/// splitmix64, used to turn a seed into a well-distributed pseudo-random stream without pulling
+/// in an RNG dependency.
+fn hash_u64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A typed block-state property, such as which way a log is oriented or how full a water block
+/// is. Each property's possible values are enumerated into a dense `0..COUNT` index so
+/// `Block::to_raw`/`Block::from_raw` can flatten every block/property combination into one
+/// contiguous `u16` state-ID range, the way block states work.
+pub trait BlockProperty: Copy + 'static {
+    const COUNT: u16;
+    fn to_index(self) -> u16;
+    fn from_index(idx: u16) -> Self;
+}
+
+/// `define_blocks! { Air = 0, Log { axis: Axis } = 1, ... }` declares the `Block` enum along
+/// with a flattened raw state-ID space: each variant may carry at most one [`BlockProperty`],
+/// and every (variant, property value) combination gets its own contiguous `u16` in
+/// `Block::to_raw`/`Block::from_raw`. The `= $id` on each variant is just the enum's own
+/// discriminant (kept for `Debug`/ordering), not the raw state ID.
 macro_rules! define_blocks {
-    ($($name:ident = $id:expr),* $(,)?) => {
+    ($($name:ident $( { $prop:ident : $prop_ty:path } )? = $id:expr),* $(,)?) => {
+        #[repr(u8)]
         #[derive(Debug, Copy, Clone, PartialEq, Eq)]
         pub enum Block {
-            $($name = $id),*
+            $($name $( { $prop: $prop_ty } )? = $id),*
         }
 
-        impl Into<Block> for u8 {
-            fn into(self) -> Block {
+        impl Block {
+            /// One past the highest valid raw state ID, i.e. the total number of distinct block
+            /// states across every variant and property combination.
+            pub fn max_raw() -> u16 {
+                0u16 $(+ { 1u16 $(* <$prop_ty as BlockProperty>::COUNT)? })*
+            }
+
+            /// Flattens this block (and its property, if any) into its raw state ID.
+            #[allow(unused_assignments)]
+            pub fn to_raw(self) -> u16 {
+                let mut base = 0u16;
+                $(
+                    if let Block::$name $( { $prop } )? = self {
+                        return base + (0u16 $(+ <$prop_ty as BlockProperty>::to_index($prop))?);
+                    }
+                    base += 1u16 $(* <$prop_ty as BlockProperty>::COUNT)?;
+                )*
+                unreachable!("Block::to_raw: no variant matched self")
+            }
+
+            /// Reconstructs a block from a raw state ID produced by [`Block::to_raw`].
+            #[allow(unused_assignments)]
+            pub fn from_raw(id: u16) -> Option<Block> {
+                let mut base = 0u16;
+                $(
+                    let count = 1u16 $(* <$prop_ty as BlockProperty>::COUNT)?;
+                    if id < base + count {
+                        return Some(Block::$name $( { $prop: <$prop_ty as BlockProperty>::from_index(id - base) } )?);
+                    }
+                    base += count;
+                )*
+                None
+            }
+
+            /// Reads this block's property if it carries one of type `P`, or `None` if this
+            /// block has no property or has a property of a different type.
+            pub fn get_property<P: BlockProperty>(&self) -> Option<P> {
                 match self {
-                    $($id => Block::$name),*,
-                    _ => Block::Air,
+                    $(
+                        $(
+                            Block::$name { $prop: value } => {
+                                (value as &dyn std::any::Any).downcast_ref::<P>().copied()
+                            }
+                        )?
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => None,
                 }
             }
-        }
 
-        impl Into<u8> for Block {
-            fn into(self) -> u8 {
-                self as u8
+            /// Returns this block with its property set to `value`, or unchanged if this block
+            /// has no property or has a property of a different type than `P`.
+            pub fn with_property<P: BlockProperty>(self, value: P) -> Self {
+                match self {
+                    $(
+                        $(
+                            Block::$name { $prop: _ } => {
+                                match (&value as &dyn std::any::Any).downcast_ref::<$prop_ty>() {
+                                    Some(new_value) => Block::$name { $prop: *new_value },
+                                    None => self,
+                                }
+                            }
+                        )?
+                    )*
+                    #[allow(unreachable_patterns)]
+                    other => other,
+                }
             }
         }
     };
 }
 
 impl World {
-    pub fn generate_empty(width: u32, height: u32, chunk_size: u32) -> Result<World, WorldError> {
+    pub fn generate_empty(
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+        min_y: i32,
+    ) -> Result<World, WorldError> {
         if width % chunk_size != 0 && height % chunk_size != 0 {
             Err(WorldError::MismatchedChunkSize)
         } else {
-            let width_chunks = width / chunk_size;
-            let height_chunks = height / chunk_size;
-            let chunks = (0..width_chunks * height_chunks)
-                .map(|idx| {
-                    let chunk_x = idx % width_chunks;
-                    let chunk_y = idx / width_chunks;
-                    Chunk::empty(chunk_size, chunk_x, chunk_y)
-                })
-                .collect();
-
             Ok(World {
                 width,
                 height,
                 chunk_size,
-                width_chunks,
-                height_chunks,
-                chunks,
+                min_y,
+                chunks: ChunkStorage::new(chunk_size, min_y),
+                listeners: Vec::new(),
+            })
+        }
+    }
+
+    /// Generates a world with procedural terrain: rolling hills around `ground_level`, water
+    /// filling dips below sea level, and the occasional tree on exposed grass. Chunks are
+    /// generated in parallel across a worker-thread pool fed over an `mpsc` queue; generation
+    /// is a pure function of `seed`, so the same seed always reproduces the same world.
+    pub fn generate_terrain(
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+        ground_level: u32,
+        seed: u64,
+    ) -> Result<World, WorldError> {
+        if ground_level > height {
+            return Err(WorldError::GroundLevelOverWorldHeight);
+        }
+        let mut world = World::generate_empty(width, height, chunk_size, 0)?;
+
+        let width_chunks = width / chunk_size;
+        let height_chunks = height / chunk_size;
+        let tasks: Vec<(u32, u32)> = (0..height_chunks)
+            .flat_map(|chunk_y| (0..width_chunks).map(move |chunk_x| (chunk_x, chunk_y)))
+            .collect();
+        let task_count = tasks.len();
+
+        let (task_tx, task_rx) = mpsc::channel::<(u32, u32)>();
+        let task_rx = Arc::new(Mutex::new(task_rx));
+        let (result_tx, result_rx) = mpsc::channel::<Chunk>();
+
+        let worker_count = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .min(task_count.max(1));
+
+        let workers: Vec<_> = (0..worker_count)
+            .map(|_| {
+                let task_rx = Arc::clone(&task_rx);
+                let result_tx = result_tx.clone();
+                thread::spawn(move || loop {
+                    let task = task_rx.lock().unwrap().recv();
+                    match task {
+                        Ok((chunk_x, chunk_y)) => {
+                            let chunk =
+                                generate_terrain_chunk(chunk_x, chunk_y, chunk_size, ground_level, seed);
+                            if result_tx.send(chunk).is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                })
             })
+            .collect();
+        drop(result_tx);
+
+        for task in tasks {
+            task_tx
+                .send(task)
+                .expect("terrain generation workers should still be alive");
+        }
+        drop(task_tx);
+
+        for _ in 0..task_count {
+            let chunk = result_rx
+                .recv()
+                .expect("a terrain generation worker stopped before finishing its chunk");
+            world.chunks.insert(chunk.chunk_x, chunk.chunk_y, chunk);
+        }
+
+        for worker in workers {
+            let _ = worker.join();
         }
+
+        Ok(world)
     }
 
-    fn check_out_of_bounds_chunk(&self, chunk_x: u32, chunk_y: u32) -> bool {
-        chunk_x > self.width / self.chunk_size || chunk_y > self.height / self.chunk_size
+    fn check_out_of_bounds_chunk(&self, chunk_x: u32, chunk_y: i32) -> bool {
+        let chunk_size = self.chunk_size as i32;
+        let min_chunk_y = self.min_y.div_euclid(chunk_size);
+        let max_chunk_y = (self.min_y + self.height as i32 - 1).div_euclid(chunk_size);
+        chunk_x > self.width / self.chunk_size || chunk_y < min_chunk_y || chunk_y > max_chunk_y
     }
-    fn check_out_of_bounds_block(&self, x: u32, y: u32) -> bool {
-        x >= self.width && y >= self.height
+    fn check_out_of_bounds_block(&self, x: u32, y: i32) -> bool {
+        x >= self.width || y < self.min_y || y >= self.min_y + self.height as i32
     }
 
-    pub fn get_chunk_mut(&mut self, chunk_x: u32, chunk_y: u32) -> Result<&mut Chunk, WorldError> {
+    pub fn get_chunk_mut(&mut self, chunk_x: u32, chunk_y: i32) -> Result<&mut Chunk, WorldError> {
         if (self.check_out_of_bounds_chunk(chunk_x, chunk_y)) {
-            Err(WorldError::OutOfBounds(chunk_x, chunk_y))
+            Err(WorldError::OutOfBounds(chunk_x as i32, chunk_y))
         } else {
-            Ok(&mut self.chunks[(chunk_y * self.height_chunks + chunk_x) as usize])
+            Ok(self.chunks.get_chunk_mut(chunk_x, chunk_y))
         }
     }
 
-    pub fn get_chunk(&self, chunk_x: u32, chunk_y: u32) -> Result<&Chunk, WorldError> {
+    pub fn get_chunk(&self, chunk_x: u32, chunk_y: i32) -> Result<&Chunk, WorldError> {
         if (self.check_out_of_bounds_chunk(chunk_x, chunk_y)) {
-            Err(WorldError::OutOfBounds(chunk_x, chunk_y))
+            Err(WorldError::OutOfBounds(chunk_x as i32, chunk_y))
         } else {
-            Ok(&self.chunks[(chunk_y * self.height_chunks + chunk_x) as usize])
+            Ok(self.chunks.get_chunk(chunk_x, chunk_y))
         }
     }
-    
-    pub fn set_block(&mut self, pos_x: u32, pos_y: u32, block: Block) -> Result<(), WorldError> {
+
+    pub fn set_block(&mut self, pos_x: u32, pos_y: i32, block: Block) -> Result<(), WorldError> {
         if (self.check_out_of_bounds_block(pos_x, pos_y)) {
-            return Err(WorldError::OutOfBounds(pos_x, pos_y));
+            return Err(WorldError::OutOfBounds(pos_x as i32, pos_y));
         }
-        
+
+        let chunk_size = self.chunk_size as i32;
         let chunk_x = pos_x / self.chunk_size;
-        let chunk_y = pos_y / self.chunk_size;
+        let chunk_y = pos_y.div_euclid(chunk_size);
         let pos_inside_chunk_x = pos_x - chunk_x * self.chunk_size;
-        let pos_inside_chunk_y = pos_y - chunk_y * self.chunk_size;
+        let pos_inside_chunk_y = pos_y.rem_euclid(chunk_size) as u32;
 
         let chunk = self.get_chunk_mut(chunk_x, chunk_y)?;
         debug!("Found chunk: {:?}", chunk);
         chunk.set_block(pos_inside_chunk_x, pos_inside_chunk_y, block);
+        self.emit(WorldEvent::BlockChange {
+            x: pos_x,
+            y: pos_y,
+            block_raw: block.to_raw(),
+        });
+        Ok(())
+    }
+
+    /// Registers a new listener and returns its receiving half. Every block change and synced
+    /// chunk from this point on is sent to it until the `Receiver` (or its owner) is dropped.
+    pub fn subscribe(&mut self) -> mpsc::Receiver<WorldEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.listeners.push(tx);
+        rx
+    }
+
+    /// Encodes the chunk at `(chunk_x, chunk_y)` and emits it as a [`WorldEvent::ChunkUpdate`],
+    /// e.g. to give a newly subscribed listener its initial view of a chunk without polling.
+    pub fn sync_chunk(&mut self, chunk_x: u32, chunk_y: i32) -> Result<(), WorldError> {
+        let data = self.get_chunk(chunk_x, chunk_y)?.encode();
+        self.emit(WorldEvent::ChunkUpdate {
+            chunk_x,
+            chunk_y,
+            data,
+        });
         Ok(())
     }
+
+    fn emit(&mut self, event: WorldEvent) {
+        self.listeners.retain(|tx| tx.send(event.clone()).is_ok());
+    }
 }
 
 impl Chunk {
-    fn empty(size: u32, chunk_x: u32, chunk_y: u32) -> Chunk {
+    fn empty(size: u32, chunk_x: u32, chunk_y: i32) -> Chunk {
         Chunk {
             size,
             chunk_x,
             chunk_y,
-            blocks: (0..size.pow(2)).map(|_| Block::Air).collect(),
+            palette: vec![Block::Air],
+            bits_per_entry: 1,
+            data: None,
+        }
+    }
+
+    pub fn get_block(&self, chunk_pos_x: u32, chunk_pos_y: u32) -> Block {
+        match &self.data {
+            None => self.palette[0],
+            Some(data) => {
+                let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
+                let palette_idx = get_packed(data, self.bits_per_entry, idx);
+                self.palette[palette_idx as usize]
+            }
         }
     }
 
     fn set_block(&mut self, chunk_pos_x: u32, chunk_pos_y: u32, block: Block) -> &mut Self {
         let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
-        self.blocks[idx] = block;
+        let entries = self.size.pow(2);
+
+        let palette_idx = match self.palette.iter().position(|b| *b == block) {
+            Some(palette_idx) => palette_idx,
+            None => {
+                self.palette.push(block);
+                self.palette.len() - 1
+            }
+        };
+        let required_bits = bits_per_entry_for(self.palette.len());
+
+        match &mut self.data {
+            None if palette_idx == 0 => {
+                // Still a single uniform block, nothing to pack.
+            }
+            None => {
+                let mut data = vec![0u64; words_for(entries, required_bits)];
+                set_packed(&mut data, required_bits, idx, palette_idx as u32);
+                self.data = Some(data);
+                self.bits_per_entry = required_bits;
+            }
+            Some(data) => {
+                if required_bits != self.bits_per_entry {
+                    *data = repack(data, self.bits_per_entry, required_bits, entries);
+                    self.bits_per_entry = required_bits;
+                }
+                set_packed(data, self.bits_per_entry, idx, palette_idx as u32);
+            }
+        }
+
         debug!(
             "[Chunk at ({}, {})] Set block index {} to {:?}",
             self.chunk_x, self.chunk_y, idx, block
         );
         self
     }
+
+    /// Encodes this chunk's paletted representation directly: size, coordinates, the palette (as
+    /// raw block state IDs), `bits_per_entry`, and the packed index words, if any. Reusing the
+    /// in-memory layout instead of expanding to one block per cell keeps this cheap even for
+    /// large, mostly-uniform chunks.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.chunk_x.to_le_bytes());
+        bytes.extend_from_slice(&self.chunk_y.to_le_bytes());
+        bytes.extend_from_slice(&(self.palette.len() as u16).to_le_bytes());
+        for block in &self.palette {
+            bytes.extend_from_slice(&block.to_raw().to_le_bytes());
+        }
+        bytes.push(self.bits_per_entry as u8);
+        match &self.data {
+            None => bytes.extend_from_slice(&0u32.to_le_bytes()),
+            Some(data) => {
+                bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                for word in data {
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Reverses [`Chunk::encode`], or returns `None` if `bytes` is truncated or names an unknown
+    /// block state ID.
+    pub fn decode(bytes: &[u8]) -> Option<Chunk> {
+        fn take<const N: usize>(bytes: &[u8], cursor: &mut usize) -> Option<[u8; N]> {
+            let slice = bytes.get(*cursor..*cursor + N)?;
+            *cursor += N;
+            slice.try_into().ok()
+        }
+
+        let mut cursor = 0usize;
+        let size = u32::from_le_bytes(take(bytes, &mut cursor)?);
+        let chunk_x = u32::from_le_bytes(take(bytes, &mut cursor)?);
+        let chunk_y = i32::from_le_bytes(take(bytes, &mut cursor)?);
+        let palette_len = u16::from_le_bytes(take(bytes, &mut cursor)?) as usize;
+
+        let mut palette = Vec::with_capacity(palette_len);
+        for _ in 0..palette_len {
+            let raw = u16::from_le_bytes(take(bytes, &mut cursor)?);
+            palette.push(Block::from_raw(raw)?);
+        }
+
+        let [bits_per_entry] = take::<1>(bytes, &mut cursor)?;
+        let bits_per_entry = bits_per_entry as u32;
+        let word_count = u32::from_le_bytes(take(bytes, &mut cursor)?) as usize;
+        let data = if word_count == 0 {
+            None
+        } else {
+            let mut words = Vec::with_capacity(word_count);
+            for _ in 0..word_count {
+                words.push(u64::from_le_bytes(take(bytes, &mut cursor)?));
+            }
+            Some(words)
+        };
+
+        Some(Chunk {
+            size,
+            chunk_x,
+            chunk_y,
+            palette,
+            bits_per_entry,
+            data,
+        })
+    }
+}
+
+/// Which way a `Log` block is oriented.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl BlockProperty for Axis {
+    const COUNT: u16 = 2;
+
+    fn to_index(self) -> u16 {
+        match self {
+            Axis::Horizontal => 0,
+            Axis::Vertical => 1,
+        }
+    }
+
+    fn from_index(idx: u16) -> Self {
+        match idx {
+            0 => Axis::Horizontal,
+            _ => Axis::Vertical,
+        }
+    }
+}
+
+/// How full a `Water` block is, from `0` (empty/source-adjacent) to `7` (full).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Level(pub u8);
+
+impl BlockProperty for Level {
+    const COUNT: u16 = 8;
+
+    fn to_index(self) -> u16 {
+        self.0 as u16
+    }
+
+    fn from_index(idx: u16) -> Self {
+        Level(idx as u8)
+    }
 }
 
 define_blocks! {
     Air = 0,
     Grass = 1,
     Stone = 2,
-    Log = 3,
+    Log { axis: Axis } = 3,
     Leaves = 4,
-    Water = 5,
+    Water { level: Level } = 5,
     Wood = 6,
 }